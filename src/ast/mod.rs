@@ -1,6 +1,6 @@
 use std::{collections::HashMap, fmt, ops::Deref};
 
-use crate::diagnostic::Span;
+use crate::{attr::Attrs, diagnostic::Span};
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Module {
@@ -32,12 +32,16 @@ pub enum ExprKind {
     List(Vec<Expr>, Option<Box<Expr>>),
     Tuple(Vec<Expr>),
     Lambda(Vec<Pattern>, Box<Expr>),
+    Unary(UnOp, Span, Box<Expr>),
     Binary(BinOp, Span, Box<Expr>, Box<Expr>),
     Try(Box<Expr>),
     Call(Box<Expr>, Box<Expr>),
     Field(Box<Expr>, String, Span),
     Match(Box<Expr>, Vec<Arm>),
+    If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
     Block(Vec<Expr>),
+    Ascription(Box<Expr>, Type),
+    Hole(Option<String>),
 }
 
 impl ExprKind {
@@ -46,6 +50,13 @@ impl ExprKind {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum UnOp {
+    Neg,
+    Not,
+    BitNot,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum BinOp {
     Add,
@@ -56,6 +67,9 @@ pub enum BinOp {
     And,
     Shl,
     Shr,
+    BitAnd,
+    BitOr,
+    BitXor,
     Or,
     Gt,
     Lt,
@@ -68,6 +82,7 @@ pub enum BinOp {
 #[derive(Clone, Debug, PartialEq)]
 pub struct Arm {
     pub pattern: Pattern,
+    pub guard: Option<Expr>,
     pub expr: Expr,
     pub span: Span,
 }
@@ -86,8 +101,14 @@ pub enum PatternKind {
     Tuple(Vec<Pattern>),
     Bool(bool),
     Int(i64),
+    Range(i64, i64),
     String(String),
+    Prefix(String, Box<Pattern>),
     List(Vec<Pattern>, Option<Box<Pattern>>),
+    Record(Path, Vec<(String, Pattern, Span)>, bool),
+    Or(Vec<Pattern>),
+    As(Box<Pattern>, String, Span),
+    Ascription(Box<Pattern>, Type),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -151,6 +172,7 @@ pub struct Function {
     pub params: Vec<Pattern>,
     pub body: Option<Expr>,
     pub span: Span,
+    pub attrs: Attrs,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -159,6 +181,7 @@ pub struct Newtype {
     pub generics: Vec<(String, Span)>,
     pub kind: NewtypeKind,
     pub span: Span,
+    pub attrs: Attrs,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -186,6 +209,7 @@ pub struct Field {
 pub struct Import {
     pub path: Path,
     pub span: Span,
+    pub attrs: Attrs,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -193,6 +217,7 @@ pub struct Ascription {
     pub name: Path,
     pub ty: Type,
     pub span: Span,
+    pub attrs: Attrs,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -200,6 +225,27 @@ pub struct Extern {
     pub name: Path,
     pub ty: Type,
     pub span: Span,
+    pub attrs: Attrs,
+}
+
+/// A trait, e.g. `trait format 'a { fn format : 'a -> str }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trait {
+    pub name: Path,
+    pub generic: (String, Span),
+    pub methods: Vec<Ascription>,
+    pub span: Span,
+    pub attrs: Attrs,
+}
+
+/// An implementation of a trait for a type, e.g. `impl format point { .. }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Impl {
+    pub r#trait: Path,
+    pub ty: Type,
+    pub functions: Vec<Function>,
+    pub span: Span,
+    pub attrs: Attrs,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -209,4 +255,32 @@ pub enum Item {
     Function(Function),
     Ascription(Ascription),
     Extern(Extern),
+    Trait(Trait),
+    Impl(Impl),
+}
+
+impl Item {
+    pub fn attrs(&self) -> &Attrs {
+        match self {
+            Item::Import(import) => &import.attrs,
+            Item::Newtype(newtype) => &newtype.attrs,
+            Item::Function(function) => &function.attrs,
+            Item::Ascription(ascription) => &ascription.attrs,
+            Item::Extern(r#extern) => &r#extern.attrs,
+            Item::Trait(r#trait) => &r#trait.attrs,
+            Item::Impl(r#impl) => &r#impl.attrs,
+        }
+    }
+
+    pub fn attrs_mut(&mut self) -> &mut Attrs {
+        match self {
+            Item::Import(import) => &mut import.attrs,
+            Item::Newtype(newtype) => &mut newtype.attrs,
+            Item::Function(function) => &mut function.attrs,
+            Item::Ascription(ascription) => &mut ascription.attrs,
+            Item::Extern(r#extern) => &mut r#extern.attrs,
+            Item::Trait(r#trait) => &mut r#trait.attrs,
+            Item::Impl(r#impl) => &mut r#impl.attrs,
+        }
+    }
 }
@@ -1,10 +1,26 @@
+use crate::{diagnostic::Span, lint::Lint, lint::LintLevel};
+
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Attrs {
     pub attrs: Vec<Attr>,
 }
 
+impl Attrs {
+    /// The level of `lint` set by an `#[allow]`, `#[warn]` or `#[deny]`
+    /// attribute, the last one taking precedence. The name `warnings` refers
+    /// to every lint.
+    pub fn lint_level(&self, lint: Lint) -> Option<LintLevel> {
+        self.attrs
+            .iter()
+            .rev()
+            .filter(|attr| attr.value == lint.name() || attr.value == "warnings")
+            .find_map(|attr| LintLevel::from_name(&attr.name))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Attr {
     pub name: String,
     pub value: String,
+    pub span: Span,
 }
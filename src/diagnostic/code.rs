@@ -0,0 +1,584 @@
+use std::fmt;
+
+/// A stable identifier for a kind of diagnostic, displayed as e.g. `E0001`.
+///
+/// Codes are never reused or renumbered, so they can be referenced from logs,
+/// allow-lists and documentation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Code(u16);
+
+impl Code {
+    /// Find a code by its name, e.g. `E0001`, ignoring case.
+    pub fn lookup(name: &str) -> Option<Self> {
+        let number = name.strip_prefix(['E', 'e'])?;
+
+        if number.len() != 4 || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        CODES
+            .iter()
+            .map(|(code, _)| *code)
+            .find(|code| number.parse() == Ok(code.0))
+    }
+
+    /// A longer explanation of the diagnostic, with examples.
+    pub fn explanation(self) -> &'static str {
+        CODES
+            .iter()
+            .find(|(code, _)| *code == self)
+            .map(|(_, explanation)| explanation.trim())
+            .expect("every code has an explanation")
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", self.0)
+    }
+}
+
+macro_rules! codes {
+    ($($name:ident = $number:literal: $explanation:expr,)*) => {
+        $(pub const $name: Code = Code($number);)*
+
+        const CODES: &[(Code, &str)] = &[$(($name, $explanation),)*];
+    };
+}
+
+codes! {
+    E0001 = 1: r#"
+The source contains a character that cannot start any token.
+
+Erroneous code example:
+
+    fn main {
+      let x = 1 $ 2
+    }
+
+Remove the character, or put it inside a string literal.
+"#,
+
+    E0002 = 2: r#"
+The parser found a token that is not allowed at this position.
+
+Erroneous code example:
+
+    fn main {
+      let = 1
+    }
+
+A `let` binding must be followed by a pattern. Check for missing or misplaced
+delimiters, keywords or names near the reported token.
+"#,
+
+    E0003 = 3: r#"
+An interpolated expression in a format string is never closed.
+
+Erroneous code example:
+
+    fn main {
+      let name = "world"
+      std::io::println "hello {name"
+    }
+
+Close the expression with `}`:
+
+    std::io::println "hello {name}"
+"#,
+
+    E0004 = 4: r#"
+An item is defined more than once in the same module.
+
+Erroneous code example:
+
+    fn add a b -> a + b
+    fn add a b -> b + a
+
+Functions, externs, types and variants share a namespace within a module, so
+each name can only be defined once. Rename or remove one of the definitions.
+"#,
+
+    E0005 = 5: r#"
+A type ascription refers to a function that is never defined.
+
+Erroneous code example:
+
+    fn add : int -> int -> int
+
+An ascription gives the type of a function defined in the same module, so it
+must be accompanied by a definition:
+
+    fn add : int -> int -> int
+    fn add a b -> a + b
+"#,
+
+    E0006 = 6: r#"
+A path refers to a module that does not exist.
+
+Erroneous code example:
+
+    fn main {
+      strng::len "hello"
+    }
+
+Check the spelling of the module, and that the package containing it is part
+of the build.
+"#,
+
+    E0007 = 7: r#"
+An import refers to an item or module that does not exist.
+
+Erroneous code example:
+
+    import std::strng
+
+Check the spelling of every segment of the import.
+"#,
+
+    E0008 = 8: r#"
+A type refers to a newtype that is not defined.
+
+Erroneous code example:
+
+    fn first : lst 'a -> 'a
+
+Check the spelling of the type, and that the module defining it is imported.
+"#,
+
+    E0009 = 9: r#"
+A newtype is given the wrong number of type parameters.
+
+Erroneous code example:
+
+    type pair 'a 'b = ('a, 'b)
+
+    fn swap : pair 'a -> pair 'a
+
+Every use of a newtype must supply exactly as many type parameters as its
+definition declares:
+
+    fn swap : pair 'a 'b -> pair 'b 'a
+"#,
+
+    E0010 = 10: r#"
+A type definition uses a generic type that it does not declare.
+
+Erroneous code example:
+
+    type wrapper = { value: 'a }
+
+Declare the generic after the name of the type:
+
+    type wrapper 'a = { value: 'a }
+"#,
+
+    E0011 = 11: r#"
+The inferred type `_` is used where a type must be written out in full.
+
+Erroneous code example:
+
+    type wrapper = { value: _ }
+
+Fields of type definitions cannot be inferred, write the type explicitly.
+"#,
+
+    E0012 = 12: r#"
+An expression refers to a name that is not in scope.
+
+Erroneous code example:
+
+    fn main {
+      let count = 1
+      std::debug::print cuont
+    }
+
+Check the spelling of the name, and that it is defined or imported before it
+is used.
+"#,
+
+    E0013 = 13: r#"
+A pattern refers to a variant that does not exist.
+
+Erroneous code example:
+
+    fn unwrap opt {
+      match opt {
+        option::sme x -> x
+        none          -> 0
+      }
+    }
+
+Check the spelling of the variant and of the module containing it.
+"#,
+
+    E0014 = 14: r#"
+A pattern contains a path with more than one segment that is not a variant.
+
+Erroneous code example:
+
+    fn get pair {
+      let pair::first = pair
+      first
+    }
+
+Bindings in patterns are always a single name. Paths with several segments
+are only allowed when they name a variant.
+"#,
+
+    E0015 = 15: r#"
+A pattern matches a value inside a variant that does not carry one.
+
+Erroneous code example:
+
+    fn unwrap opt {
+      match opt {
+        some x -> x
+        none y -> y
+      }
+    }
+
+Variants without a type can only be matched by name:
+
+    none -> 0
+"#,
+
+    E0016 = 16: r#"
+A pattern that might not match is used where every value must match.
+
+Erroneous code example:
+
+    fn main {
+      let some x = some 1
+    }
+
+`let` bindings, lambda parameters and function parameters must be
+irrefutable. Use a `match` expression to handle the other cases:
+
+    match some 1 {
+      some x -> x
+      none   -> 0
+    }
+"#,
+
+    E0017 = 17: r#"
+A record expression names a type that is not a record.
+
+Erroneous code example:
+
+    type option 'a = some 'a | none
+
+    fn main {
+      let x = option { value: 1 }
+    }
+
+Only types defined as records, like `type point = { x: int; y: int }`, can be
+constructed with the record syntax.
+"#,
+
+    E0018 = 18: r#"
+A field is accessed or initialized that the type does not have.
+
+Erroneous code example:
+
+    type point = { x: int; y: int }
+
+    fn main {
+      let p = point { x: 1; z: 2 }
+    }
+
+Check the spelling of the field against the definition of the type.
+"#,
+
+    E0019 = 19: r#"
+A field is accessed on a value whose type has no fields.
+
+Erroneous code example:
+
+    fn main {
+      let x = 1
+      x.value
+    }
+
+Only records have fields.
+"#,
+
+    E0020 = 20: r#"
+A field is initialized or matched more than once.
+
+Erroneous code example:
+
+    type point = { x: int; y: int }
+
+    fn main {
+      let p = point { x: 1; x: 2; y: 3 }
+    }
+
+Each field of a record, `with` expression or record pattern can only be given
+once.
+"#,
+
+    E0021 = 21: r#"
+A record expression does not initialize every field of the record.
+
+Erroneous code example:
+
+    type point = { x: int; y: int }
+
+    fn main {
+      let p = point { x: 1 }
+    }
+
+Every field must be given a value:
+
+    let p = point { x: 1; y: 2 }
+
+A record pattern must also list every field, unless it ends with `..`:
+
+    let point { x; .. } = p
+"#,
+
+    E0022 = 22: r#"
+A `match` expression does not cover every possible value.
+
+Erroneous code example:
+
+    fn describe opt {
+      match opt {
+        some x -> "some"
+      }
+    }
+
+Add arms for the missing cases, or a wildcard arm that matches anything:
+
+    match opt {
+      some x -> "some"
+      _      -> "none"
+    }
+"#,
+
+    E0023 = 23: r#"
+Two types were expected to be the same, but they are not.
+
+Erroneous code example:
+
+    fn main {
+      let x = 1 + "one"
+    }
+
+The labels point at where each of the types comes from. Convert one of the
+values, or change the code so the types agree.
+"#,
+
+    E0024 = 24: r#"
+An arithmetic operation is applied to a type that is not a number.
+
+Erroneous code example:
+
+    fn main {
+      let x = "one" - "two"
+    }
+
+Arithmetic operators only work on `int`. To join strings, use a format string
+like `"{a}{b}"`.
+"#,
+
+    E0025 = 25: r#"
+An expression contains a hole, written `_` or `?name`.
+
+Example:
+
+    fn main {
+      let xs = [1; 2; 3]
+      std::list::map ?f xs
+    }
+
+Holes let a program be type checked before it is finished. The compiler
+reports the type the hole must have, here `int -> 'a`, together with the
+locals in scope and their types. Replace the hole with an expression of that
+type to build the program.
+"#,
+
+    E0026 = 26: r#"
+A type would have to contain itself, making it infinitely large.
+
+Erroneous code example:
+
+    fn apply-self f -> f f
+
+For `f f` to be valid, `f` must be a function taking `f` as input, so its type
+`'a` would have to be `'a -> 'b`, which expands to `('a -> 'b) -> 'b` and so
+on forever. Usually this means an argument is missing or passed in the wrong
+place.
+"#,
+
+    E0027 = 27: r#"
+Values of a type that cannot be compared for equality are compared with `==`
+or `!=`.
+
+Erroneous code example:
+
+    fn main {
+      let f = |x| x + 1
+      let same = f == f
+    }
+
+Functions cannot be compared, and neither can opaque types like maps and
+channels. Records, unions, tuples and lists can be compared if everything they
+contain can.
+"#,
+
+    E0028 = 28: r#"
+Values of a type that cannot be ordered are compared with `<`, `>`, `<=` or
+`>=`.
+
+Erroneous code example:
+
+    fn main {
+      let smaller = (1, 2) < (3, 4)
+    }
+
+Only `int`, `str` and types implementing `std::ord` can be ordered. To order
+other values, implement `std::ord` for them or compare their parts one by one.
+"#,
+
+    E0029 = 29: r#"
+A trait method is used with a type that does not implement the trait.
+
+Erroneous code example:
+
+    trait show 'a {
+      fn show : 'a -> str
+    }
+
+    fn main {
+      let text = show 1
+    }
+
+Add an impl of the trait for the type:
+
+    impl show int {
+      fn show n -> "{n}"
+    }
+
+Types without an impl of `std::eq`, `std::hash` or `std::format` are compared,
+hashed and formatted by their contents instead. The built-in traits can only be
+implemented for newtypes declared outside of `std`, see E0037.
+"#,
+
+    E0030 = 30: r#"
+The methods of an impl do not match the methods of its trait.
+
+Erroneous code example:
+
+    trait show 'a {
+      fn show : 'a -> str
+    }
+
+    impl show int {
+      fn display n -> "{n}"
+    }
+
+An impl must define every method of its trait, and no other functions. Every
+method of a trait must also mention the type of the trait, so that the impl it
+refers to can be found from how it is used.
+"#,
+
+    E0031 = 31: r#"
+A trait is implemented more than once for the same type.
+
+Erroneous code example:
+
+    impl show (option int) {
+      fn show _ -> "option"
+    }
+
+    impl show (option str) {
+      fn show _ -> "option"
+    }
+
+Impls are chosen by the outermost type constructor alone, so `option int` and
+`option str` overlap. Write a single impl for `option 'a` instead.
+"#,
+
+    E0032 = 32: r#"
+An impl refers to a trait that does not exist.
+
+Erroneous code example:
+
+    impl shwo int {
+      fn show n -> "{n}"
+    }
+
+Check the spelling of the trait, and that it is imported.
+"#,
+
+    E0033 = 33: r#"
+A trait is implemented for a type variable.
+
+Erroneous code example:
+
+    impl show 'a {
+      fn show _ -> "value"
+    }
+
+An impl for every type would overlap with every other impl of the trait.
+Implement the trait for each type instead.
+"#,
+
+    E0034 = 34: r#"
+A variable is bound in some alternatives of an or-pattern, but not in others.
+
+Erroneous code example:
+
+    match pair {
+      (0, x) | (x, 0) -> x
+      (x, _) | (_, 0) -> x
+      _ -> 0
+    }
+
+The second arm binds `x` in its first alternative but not in its second, so
+`x` would have no value when the second alternative matches. Every
+alternative must bind the same variables, at the same types.
+"#,
+
+    E0035 = 35: r#"
+A range pattern has a lower bound greater than its upper bound.
+
+Erroneous code example:
+
+    match digit {
+      9..=0 -> "digit"
+      _ -> "other"
+    }
+
+Range patterns are inclusive, and go from the lower bound to the upper bound.
+A range whose lower bound is greater than its upper bound matches nothing.
+"#,
+
+    E0036 = 36: r#"
+An integer is divided by, or takes the remainder of, a literal zero.
+
+Erroneous code example:
+
+    let half = count / 0
+
+Dividing by zero panics at runtime, as does `%` with a divisor of zero.
+"#,
+
+    E0037 = 37: r#"
+A built-in trait is implemented for a type that is not a newtype declared
+outside of `std`.
+
+Erroneous code example:
+
+    impl std::format int {
+      fn format n -> "int {n}"
+    }
+
+The runtime finds the methods of `std::eq`, `std::ord`, `std::hash` and
+`std::format` on the values of a newtype, and values of other types, or
+values created by `std`, cannot carry them. Declare a newtype wrapping the
+value and implement the trait for it instead.
+"#,
+}
@@ -1,6 +1,8 @@
 use std::panic::Location;
 
-use super::{Sources, Span};
+use crate::lint::Lint;
+
+use super::{Code, Sources, Span};
 
 pub trait Emitter {
     fn emit(&mut self, diagnostic: Diagnostic);
@@ -68,8 +70,12 @@ pub enum Level {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Diagnostic {
     pub level: Level,
+    pub code: Option<Code>,
+    pub lint: Option<Lint>,
     pub message: String,
     pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
     pub location: &'static Location<'static>,
 }
 
@@ -77,6 +83,10 @@ pub struct Diagnostic {
 pub struct Label {
     pub message: Option<String>,
     pub span: Span,
+
+    /// Whether this label points at the cause of the diagnostic, as opposed to
+    /// providing additional context.
+    pub primary: bool,
 }
 
 impl Diagnostic {
@@ -84,8 +94,12 @@ impl Diagnostic {
     pub fn new(level: Level, message: impl ToString) -> Self {
         Self {
             level,
+            code: None,
+            lint: None,
             message: message.to_string(),
             labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
             location: Location::caller(),
         }
     }
@@ -105,10 +119,21 @@ impl Diagnostic {
         Self::new(Level::Note, message)
     }
 
+    pub fn with_code(mut self, code: Code) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_lint(mut self, lint: Lint) -> Self {
+        self.lint = Some(lint);
+        self
+    }
+
     pub fn with_label(mut self, span: Span, message: impl ToString) -> Self {
         self.labels.push(Label {
             message: Some(message.to_string()),
             span,
+            primary: true,
         });
 
         self
@@ -118,36 +143,84 @@ impl Diagnostic {
         self.labels.push(Label {
             message: None,
             span,
+            primary: true,
         });
 
         self
     }
 
+    pub fn with_secondary_label(mut self, span: Span, message: impl ToString) -> Self {
+        self.labels.push(Label {
+            message: Some(message.to_string()),
+            span,
+            primary: false,
+        });
+
+        self
+    }
+
+    pub fn with_note(mut self, note: impl ToString) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl ToString) -> Self {
+        self.help.push(help.to_string());
+        self
+    }
+
     pub fn print(&self, sources: &Sources) {
-        use colors::*;
+        let palette = colors::Palette::detect();
+
+        let Palette {
+            reset, bold, blue, ..
+        } = palette;
 
-        let (color, level) = match self.level {
-            Level::Error => (RED, "error"),
-            Level::Warn => (YELLOW, "warning"),
-            Level::Note => (BLUE, "note"),
+        let color = palette.level(self.level);
+        let level = match self.code {
+            Some(code) => format!("{}[{code}]", self.level.as_str()),
+            None => self.level.as_str().to_string(),
         };
 
-        eprintln!("{color}{level}{RESET}: {BOLD}{}{RESET}", self.message);
+        eprintln!("{color}{level}{reset}: {bold}{}{reset}", self.message);
 
         let mut indent = 0;
 
         for label in &self.labels {
             let source = &sources[label.span.id];
-            let (line, _) = label.span.compute_start_line_column(&source.content);
+            let (line, _) = label.span.compute_end_line_column(&source.content);
 
             indent = indent.max(line.to_string().chars().count());
         }
 
         let mut prev_path = None;
+        let mut printed = vec![false; self.labels.len()];
+
+        for (i, label) in self.labels.iter().enumerate() {
+            if printed[i] {
+                continue;
+            }
 
-        for label in &self.labels {
             let source = &sources[label.span.id];
             let (line, column) = label.span.compute_start_line_column(&source.content);
+            let (end_line, _) = label.span.compute_end_line_column(&source.content);
+
+            // single line labels on the same line are drawn under one copy of it
+            let mut group = vec![label];
+
+            for (j, other) in self.labels.iter().enumerate().skip(i + 1) {
+                if line != end_line || printed[j] || other.span.id != label.span.id {
+                    continue;
+                }
+
+                let (other_line, _) = other.span.compute_start_line_column(&source.content);
+                let (other_end_line, _) = other.span.compute_end_line_column(&source.content);
+
+                if other_line == line && other_end_line == line {
+                    group.push(other);
+                    printed[j] = true;
+                }
+            }
 
             if prev_path != Some(&source.path) {
                 let sep = match prev_path {
@@ -156,44 +229,222 @@ impl Diagnostic {
                 };
 
                 eprintln!(
-                    "{}{BLUE}{BOLD}{sep}{RESET} {}:{line}:{column}",
+                    "{}{blue}{bold}{sep}{reset} {}:{line}:{column}",
                     " ".repeat(indent),
                     source.path.display(),
                 );
             } else {
-                eprintln!("{}{BLUE}{BOLD}...{RESET}", " ".repeat(indent));
+                eprintln!("{}{blue}{bold}...{reset}", " ".repeat(indent));
             }
 
             prev_path = Some(&source.path);
 
-            let content = source.content.lines().nth(line as usize - 1).unwrap();
-            let length = (label.span.hi - label.span.lo) as usize;
+            eprintln!("{} {blue}{bold}|{reset}", " ".repeat(indent));
 
-            eprintln!("{} {BLUE}{BOLD}|{RESET}", " ".repeat(indent));
+            match group.len() {
+                1 => print_label(&palette, self.level, label, &source.content, indent),
+                _ => print_line_labels(&palette, self.level, &group, &source.content, indent),
+            }
 
-            eprintln!(
-                "{BLUE}{BOLD}{line}{} |{RESET} {content}",
-                " ".repeat(indent - line.to_string().chars().count()),
-            );
+            eprintln!("{} {blue}{bold}|{reset}", " ".repeat(indent));
+        }
+
+        let notes = self.notes.iter().map(|note| ("note", note));
+        let help = self.help.iter().map(|help| ("help", help));
+
+        for (kind, message) in notes.chain(help) {
+            // align the following lines of the message with the first one
+            let align = format!("\n{}", " ".repeat(indent + kind.len() + 5));
+            let message = message.replace('\n', &align);
 
             eprintln!(
-                "{} {BLUE}{BOLD}|{RESET} {}{color}{} {}{RESET}",
+                "{} {blue}{bold}={reset} {bold}{kind}{reset}: {message}",
                 " ".repeat(indent),
-                " ".repeat(column as usize - 1),
-                "^".repeat(length.min(content.len() + 1 - column as usize)),
-                label.message.as_deref().unwrap_or(""),
             );
+        }
+    }
+}
+
+/// The number of lines of a multi-line label shown before and after the
+/// elided middle.
+const CONTEXT_LINES: u32 = 2;
 
-            eprintln!("{} {BLUE}{BOLD}|{RESET}", " ".repeat(indent));
+/// Print a line of source with the markers of several labels under it.
+fn print_line_labels(
+    palette: &Palette,
+    level: Level,
+    labels: &[&Label],
+    content: &str,
+    indent: usize,
+) {
+    let Palette {
+        reset, bold, blue, ..
+    } = *palette;
+
+    let (line, _) = labels[0].span.compute_start_line_column(content);
+    let source_line = content.lines().nth(line as usize - 1).unwrap_or("");
+
+    eprintln!("{blue}{bold}{line:indent$} |{reset} {source_line}");
+
+    let mut labels = labels.to_vec();
+    labels.sort_by_key(|label| label.span.lo);
+
+    for label in labels {
+        print_marker(palette, level, label, content, indent);
+    }
+}
+
+/// Print the marker under a single line label, followed by its message.
+fn print_marker(palette: &Palette, level: Level, label: &Label, content: &str, indent: usize) {
+    let Palette {
+        reset, bold, blue, ..
+    } = *palette;
+
+    let (_, column) = label.span.compute_start_line_column(content);
+    let (_, end_column) = label.span.compute_end_line_column(content);
+
+    let (color, marker) = match label.primary {
+        true => (palette.level(level), '^'),
+        false => (blue, '-'),
+    };
+
+    let message = label.message.as_deref().unwrap_or("");
+    let length = end_column.saturating_sub(column).max(1);
+
+    eprintln!(
+        "{blue}{bold}{:indent$} |{reset} {}{color}{} {message}{reset}",
+        "",
+        " ".repeat(column as usize - 1),
+        marker.to_string().repeat(length as usize),
+    );
+}
+
+fn print_label(palette: &Palette, level: Level, label: &Label, content: &str, indent: usize) {
+    let Palette {
+        reset, bold, blue, ..
+    } = *palette;
+
+    let (line, column) = label.span.compute_start_line_column(content);
+    let (end_line, end_column) = label.span.compute_end_line_column(content);
+
+    let (color, marker) = match label.primary {
+        true => (palette.level(level), '^'),
+        false => (blue, '-'),
+    };
+
+    let message = label.message.as_deref().unwrap_or("");
+
+    let gutter = |line: Option<u32>| {
+        let number = line.map_or(String::new(), |line| line.to_string());
+        format!("{blue}{bold}{number:indent$} |{reset}")
+    };
+
+    let source_line = |line: u32| content.lines().nth(line as usize - 1).unwrap_or("");
+
+    if line == end_line {
+        eprintln!("{} {}", gutter(Some(line)), source_line(line));
+        print_marker(palette, level, label, content, indent);
+
+        return;
+    }
+
+    // the start of the span is marked with a line pointing at the first column
+    eprintln!("{}   {}", gutter(Some(line)), source_line(line));
+    eprintln!(
+        "{}  {color}{}{marker}{reset}",
+        gutter(None),
+        "_".repeat(column as usize),
+    );
+
+    for current in line + 1..=end_line {
+        let elided = end_line - line > CONTEXT_LINES * 2 + 1
+            && current > line + CONTEXT_LINES
+            && current < end_line - CONTEXT_LINES + 1;
+
+        if elided {
+            if current == line + CONTEXT_LINES + 1 {
+                eprintln!("{blue}{bold}...{reset}");
+            }
+
+            continue;
         }
+
+        eprintln!(
+            "{} {color}|{reset} {}",
+            gutter(Some(current)),
+            source_line(current),
+        );
     }
+
+    // the end of the span is marked with a line pointing at the last column
+    eprintln!(
+        "{} {color}|{}{marker} {message}{reset}",
+        gutter(None),
+        "_".repeat(end_column.saturating_sub(1).max(1) as usize),
+    );
 }
 
+use colors::Palette;
+
 mod colors {
-    pub const RESET: &str = "\x1b[0m";
-    pub const BOLD: &str = "\x1b[1m";
+    use std::{
+        env,
+        io::{self, IsTerminal},
+    };
+
+    use super::Level;
+
+    const RESET: &str = "\x1b[0m";
+    const BOLD: &str = "\x1b[1m";
+
+    const RED: &str = "\x1b[31m";
+    const YELLOW: &str = "\x1b[33m";
+    const BLUE: &str = "\x1b[34m";
+
+    #[derive(Clone, Copy)]
+    pub struct Palette {
+        pub reset: &'static str,
+        pub bold: &'static str,
+
+        pub red: &'static str,
+        pub yellow: &'static str,
+        pub blue: &'static str,
+    }
+
+    impl Palette {
+        const COLORED: Self = Self {
+            reset: RESET,
+            bold: BOLD,
+            red: RED,
+            yellow: YELLOW,
+            blue: BLUE,
+        };
+
+        const PLAIN: Self = Self {
+            reset: "",
+            bold: "",
+            red: "",
+            yellow: "",
+            blue: "",
+        };
+
+        /// Colors are only emitted when stderr is a terminal and `NO_COLOR` is
+        /// not set, see <https://no-color.org>.
+        pub fn detect() -> Self {
+            let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+            match !no_color && io::stderr().is_terminal() {
+                true => Self::COLORED,
+                false => Self::PLAIN,
+            }
+        }
 
-    pub const RED: &str = "\x1b[31m";
-    pub const YELLOW: &str = "\x1b[33m";
-    pub const BLUE: &str = "\x1b[34m";
+        pub fn level(&self, level: Level) -> &'static str {
+            match level {
+                Level::Error => self.red,
+                Level::Warn => self.yellow,
+                Level::Note => self.blue,
+            }
+        }
+    }
 }
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::lint::Lint;
+
+use super::{Diagnostic, Label, Level, Sources};
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'a> {
+    level: &'static str,
+    code: Option<String>,
+    lint: Option<&'static str>,
+    message: &'a str,
+    labels: Vec<JsonLabel<'a>>,
+    notes: &'a [String],
+    help: &'a [String],
+}
+
+#[derive(Serialize)]
+struct JsonLabel<'a> {
+    message: Option<&'a str>,
+    primary: bool,
+    file: &'a Path,
+    line: u32,
+    column: u32,
+    end_line: u32,
+    end_column: u32,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warning",
+            Level::Note => "note",
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Serialize the diagnostic as a single line of JSON.
+    ///
+    /// Lines and columns are 1-based, and columns are counted in characters.
+    pub fn to_json(&self, sources: &Sources) -> String {
+        let labels = self
+            .labels
+            .iter()
+            .map(|label| json_label(label, sources))
+            .collect();
+
+        let diagnostic = JsonDiagnostic {
+            level: self.level.as_str(),
+            code: self.code.map(|code| code.to_string()),
+            lint: self.lint.map(Lint::name),
+            message: &self.message,
+            labels,
+            notes: &self.notes,
+            help: &self.help,
+        };
+
+        serde_json::to_string(&diagnostic).expect("diagnostic serialization cannot fail")
+    }
+}
+
+fn json_label<'a>(label: &'a Label, sources: &'a Sources) -> JsonLabel<'a> {
+    let source = &sources[label.span.id];
+    let (line, column) = label.span.compute_start_line_column(&source.content);
+    let (end_line, end_column) = label.span.compute_end_line_column(&source.content);
+
+    JsonLabel {
+        message: label.message.as_deref(),
+        primary: label.primary,
+        file: &source.path,
+        line,
+        column,
+        end_line,
+        end_column,
+    }
+}
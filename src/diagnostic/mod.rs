@@ -1,5 +1,10 @@
+pub mod code;
 mod diagnostic;
+mod json;
 mod span;
+mod suggest;
 
+pub use code::Code;
 pub use diagnostic::*;
 pub use span::*;
+pub use suggest::*;
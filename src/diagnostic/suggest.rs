@@ -0,0 +1,103 @@
+/// Find the candidate closest to `name`, if any is close enough to likely be a
+/// typo of it.
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// The edit distance between `a` and `b`, counted in characters, where
+/// insertions, deletions, substitutions and transpositions of adjacent
+/// characters each cost one.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, suggest};
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("map", "map"), 0);
+        assert_eq!(edit_distance("map", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn distance_of_transposition() {
+        assert_eq!(edit_distance("fitler", "filter"), 1);
+        assert_eq!(edit_distance("ab", "ba"), 1);
+    }
+
+    #[test]
+    fn distance_counts_characters() {
+        assert_eq!(edit_distance("café", "cafe"), 1);
+        assert_eq!(edit_distance("🦀", "🐍"), 1);
+    }
+
+    #[test]
+    fn suggest_missing_dash() {
+        let candidates = ["filter", "filter-map", "map"];
+        assert_eq!(suggest("filtermap", candidates), Some("filter-map"));
+    }
+
+    #[test]
+    fn suggest_closest() {
+        let candidates = ["lenght", "length", "lengths"];
+        assert_eq!(suggest("lenth", candidates), Some("length"));
+    }
+
+    #[test]
+    fn suggest_ignores_name() {
+        assert_eq!(suggest("map", ["map"]), None);
+    }
+
+    #[test]
+    fn suggest_max_distance() {
+        // a third of the length, but at least one
+        assert_eq!(suggest("ab", ["ax"]), Some("ax"));
+        assert_eq!(suggest("ab", ["xy"]), None);
+
+        assert_eq!(suggest("format", ["fromat"]), Some("fromat"));
+        assert_eq!(suggest("format", ["formal"]), Some("formal"));
+        assert_eq!(suggest("format", ["fonmal"]), Some("fonmal"));
+        assert_eq!(suggest("format", ["forums"]), None);
+    }
+}
@@ -1,10 +1,13 @@
 use std::marker::PhantomData;
 
+use crate::diagnostic::Span;
+
 use super::{Expr, Pattern, arena::impl_arena};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Body<T> {
     pub name: String,
+    pub span: Span,
     pub locals: Locals<T>,
     pub inputs: Vec<Pattern<T>>,
     pub expr: Option<Expr<T>>,
@@ -14,6 +17,7 @@ pub struct Body<T> {
 #[derive(Clone, Debug, PartialEq, Hash)]
 pub struct Local<T> {
     pub name: String,
+    pub span: Span,
     pub ty: T,
 }
 
@@ -19,10 +19,21 @@ pub enum ExprKind<T> {
     With(Box<Expr<T>>, Vec<(String, Expr<T>)>),
     Try(Box<Expr<T>>),
     Call(Box<Expr<T>>, Box<Expr<T>>),
+    Unary(UnOp, Box<Expr<T>>),
     Binary(BinOp, Box<Expr<T>>, Box<Expr<T>>),
     Match(Box<Expr<T>>, Vec<Arm<T>>),
     Field(Box<Expr<T>>, String),
     Block(Vec<Expr<T>>),
+
+    /// A hole left to be filled in, rejected after type checking.
+    Hole,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UnOp {
+    Neg,
+    Not,
+    BitNot,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -35,6 +46,9 @@ pub enum BinOp {
     And,
     Shl,
     Shr,
+    BitAnd,
+    BitOr,
+    BitXor,
     Or,
     Gt,
     Lt,
@@ -47,6 +61,7 @@ pub enum BinOp {
 #[derive(Clone, Debug, PartialEq)]
 pub struct Arm<T> {
     pub pattern: Pattern<T>,
+    pub guard: Option<Expr<T>>,
     pub expr: Expr<T>,
 }
 
@@ -70,25 +85,13 @@ pub enum PatternKind<T> {
     Tuple(Vec<Pattern<T>>),
     Bool(bool),
     Int(i64),
+    Range(i64, i64),
     String(String),
+    Prefix(String, Box<Pattern<T>>),
     Variant(T, String, Option<Box<Pattern<T>>>),
     ListEmpty,
     ListCons(Box<Pattern<T>>, Box<Pattern<T>>),
-}
-
-impl<T> PatternKind<T> {
-    pub fn is_refutable(&self) -> bool {
-        match self {
-            Self::Wildcard | Self::Binding(_) => false,
-
-            Self::Bool(_)
-            | Self::Int(_)
-            | Self::String(_)
-            | Self::ListEmpty
-            | Self::ListCons(_, _)
-            | Self::Variant(_, _, _) => true,
-
-            Self::Tuple(patterns) => patterns.iter().any(|p| p.kind.is_refutable()),
-        }
-    }
+    Record(T, Vec<(String, Pattern<T>)>),
+    Or(Vec<Pattern<T>>),
+    As(Box<Pattern<T>>, Lid<T>),
 }
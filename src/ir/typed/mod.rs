@@ -1,10 +1,13 @@
 mod r#type;
 
+use std::collections::HashMap;
+
 pub use r#type::*;
 
 pub type Expr = super::Expr<Type>;
 pub type ExprKind = super::ExprKind<Type>;
 pub type Arm = super::Arm<Type>;
+pub type UnOp = super::UnOp;
 pub type BinOp = super::BinOp;
 pub type Pattern = super::Pattern<Type>;
 pub type PatternKind = super::PatternKind<Type>;
@@ -21,6 +24,10 @@ pub type Bid = super::Bid<Type>;
 pub struct Program {
     pub bodies: Bodies,
     pub types: Types,
+
+    /// The methods of built-in traits implemented by each newtype, which are
+    /// called by the runtime.
+    pub methods: HashMap<Tid, Vec<(String, Bid)>>,
 }
 
 impl Default for Program {
@@ -34,6 +41,7 @@ impl Program {
         Self {
             bodies: Bodies::default(),
             types: Types::default(),
+            methods: HashMap::new(),
         }
     }
 }
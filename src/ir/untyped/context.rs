@@ -4,9 +4,9 @@ use std::{
     ops::{Index, IndexMut},
 };
 
-use crate::diagnostic::{Diagnostic, Emitter, Span};
+use crate::diagnostic::{Diagnostic, Emitter, Span, code, suggest};
 
-use super::{App, AppKind, Type, Var};
+use super::{App, AppKind, Bid, Type, Var};
 
 #[derive(Debug)]
 pub struct TypeError;
@@ -14,10 +14,18 @@ pub struct TypeError;
 #[derive(Clone, Debug)]
 pub struct TypeContext {
     newtypes: Vec<Newtype>,
+    traits: Vec<Trait>,
+    impls: Vec<Impl>,
     bounds: HashMap<Var, Bounds>,
     subst: HashMap<Var, Type>,
     cache: HashSet<(Type, Type)>,
     errors: Vec<Diagnostic>,
+
+    /// The outermost types being unified, used to report mismatches deep
+    /// inside them.
+    unifying: Option<(Type, Type)>,
+
+    pub lang: LangTraits,
 }
 
 impl Default for TypeContext {
@@ -30,10 +38,14 @@ impl TypeContext {
     pub fn new() -> Self {
         TypeContext {
             newtypes: Vec::new(),
+            traits: Vec::new(),
+            impls: Vec::new(),
             bounds: HashMap::new(),
             subst: HashMap::new(),
             cache: HashSet::new(),
             errors: Vec::new(),
+            unifying: None,
+            lang: LangTraits::default(),
         }
     }
 
@@ -43,6 +55,82 @@ impl TypeContext {
         Tid { index }
     }
 
+    pub fn push_trait(&mut self, r#trait: Trait) -> Trid {
+        let index = self.traits.len() as u64;
+        self.traits.push(r#trait);
+        Trid { index }
+    }
+
+    pub fn push_impl(&mut self, r#impl: Impl) {
+        self.impls.push(r#impl);
+    }
+
+    pub fn impls(&self) -> &[Impl] {
+        &self.impls
+    }
+
+    /// Get the trait method declared by the body `bid`, if it is one.
+    pub fn method(&self, bid: Bid) -> Option<(Trid, &Method)> {
+        self.traits.iter().enumerate().find_map(|(index, r#trait)| {
+            let method = r#trait.methods.iter().find(|method| method.body == bid)?;
+            Some((
+                Trid {
+                    index: index as u64,
+                },
+                method,
+            ))
+        })
+    }
+
+    /// Whether an impl of the trait `trid` for `ty` may be written.
+    ///
+    /// The runtime calls built-in traits through the values of newtypes,
+    /// which only code outside of `std` constructs.
+    pub fn can_implement(&self, trid: Trid, ty: &Type) -> bool {
+        if !self.lang.contains(trid) {
+            return true;
+        }
+
+        match ty {
+            Type::App(App {
+                kind: AppKind::Newtype(tid, _),
+                ..
+            }) => !self[*tid].name.starts_with("std::"),
+            _ => false,
+        }
+    }
+
+    /// Find the impl of the trait `trid` for `ty`, by the outermost type
+    /// constructor of `ty`.
+    pub fn find_impl(&self, trid: Trid, ty: &Type) -> Option<&Impl> {
+        let Type::App(app) = self.unalias(ty.clone()) else {
+            return None;
+        };
+
+        self.impls.iter().find(|r#impl| {
+            let Type::App(ref head) = r#impl.ty else {
+                return false;
+            };
+
+            r#impl.r#trait == trid && same_head(&head.kind, &app.kind)
+        })
+    }
+
+    /// Substitute `ty` until it is neither a solved variable nor an alias.
+    pub fn unalias(&self, ty: Type) -> Type {
+        if let Some(ty) = self.substitute_shallow(&ty) {
+            return self.unalias(ty);
+        }
+
+        match ty {
+            Type::App(ref app) if let Some(aliased) = self.substitute_alias(app) => {
+                self.unalias(aliased)
+            }
+
+            ty => ty,
+        }
+    }
+
     pub fn bounds_mut(&mut self, var: Var) -> &mut Bounds {
         self.bounds.entry(var).or_default()
     }
@@ -94,6 +182,7 @@ impl TypeContext {
                         "type `{}` does not support fields",
                         self.format_type(&target),
                     ))
+                    .with_code(code::E0019)
                     .with_label(span, "arising from here");
 
                     self.errors.push(diagnostic);
@@ -101,13 +190,20 @@ impl TypeContext {
                 };
 
                 let Some(field) = record.field(name) else {
-                    let diagnostic = Diagnostic::error(format!(
+                    let mut diagnostic = Diagnostic::error(format!(
                         "type `{}` does not have a field named `{}`",
                         self.format_type(&target),
                         name,
                     ))
+                    .with_code(code::E0018)
                     .with_label(span, "arising from here");
 
+                    let names = record.fields.iter().map(|field| field.name.as_str());
+
+                    if let Some(suggestion) = suggest(name, names) {
+                        diagnostic = diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+                    }
+
                     self.errors.push(diagnostic);
                     return;
                 };
@@ -128,6 +224,7 @@ impl TypeContext {
                     "type `{}` does not support fields",
                     self.format_type(&target),
                 ))
+                .with_code(code::E0019)
                 .with_label(span, "arising from here");
 
                 self.errors.push(diagnostic);
@@ -155,6 +252,7 @@ impl TypeContext {
                     "type `{}` is not a number",
                     self.format_type(&target),
                 ))
+                .with_code(code::E0024)
                 .with_label(span, "arising from here");
 
                 self.errors.push(diagnostic);
@@ -162,11 +260,234 @@ impl TypeContext {
         }
     }
 
+    /// Require values of `target` to be comparable with `==` and `!=`.
+    pub fn equatable(&mut self, target: Type, span: Span) {
+        let Err(part) = self.equatable_impl(target.clone(), &mut HashSet::new()) else {
+            return;
+        };
+
+        let types = self.format_types(&[target, part]);
+        let [target, part] = &types[..] else {
+            unreachable!();
+        };
+
+        let mut diagnostic =
+            Diagnostic::error(format!("type `{target}` cannot be compared for equality",))
+                .with_code(code::E0027)
+                .with_label(span, "arising from here");
+
+        if target != part {
+            diagnostic = diagnostic.with_note(format!("`{part}` cannot be compared"));
+        }
+
+        self.errors.push(diagnostic);
+    }
+
+    /// Check that `target` is equatable, returning the part of it that is not.
+    fn equatable_impl(&mut self, target: Type, checking: &mut HashSet<String>) -> Result<(), Type> {
+        if let Some(subst_ty) = self.substitute_shallow(&target) {
+            return self.equatable_impl(subst_ty, checking);
+        }
+
+        let Type::App(ref app) = target else {
+            if let Type::Var(var) = target {
+                self.bounds_mut(var).eq = true;
+            }
+
+            return Ok(());
+        };
+
+        match app.kind {
+            AppKind::Int | AppKind::Bool | AppKind::Str | AppKind::Unit => Ok(()),
+
+            AppKind::List(ref element) => self.equatable_impl((**element).clone(), checking),
+
+            AppKind::Tuple(ref items) => items
+                .iter()
+                .try_for_each(|item| self.equatable_impl(item.clone(), checking)),
+
+            AppKind::Function(..) => Err(target.clone()),
+
+            AppKind::Newtype(tid, ref generics) => {
+                if let Some(aliased) = self.substitute_alias(app) {
+                    return self.equatable_impl(aliased, checking);
+                }
+
+                // a type with its own equality is equatable whatever it contains
+                if let Some(eq) = self.lang.eq
+                    && self.find_impl(eq, &target).is_some()
+                {
+                    return Ok(());
+                }
+
+                // a recursive type is equatable if the rest of it is
+                if !checking.insert(self.substitute(target.clone()).to_string()) {
+                    return Ok(());
+                }
+
+                let subst = self[tid]
+                    .generics
+                    .iter()
+                    .map(|(_, var)| *var)
+                    .zip(generics.iter().cloned())
+                    .collect::<HashMap<_, _>>();
+
+                let parts: Vec<_> = match self[tid].kind {
+                    // records without fields are opaque, like maps and channels
+                    NewtypeKind::Record(ref record) if record.fields.is_empty() => {
+                        return Err(target.clone());
+                    }
+
+                    NewtypeKind::Record(ref record) => {
+                        record.fields.iter().map(|field| field.ty.clone()).collect()
+                    }
+
+                    NewtypeKind::Union(ref union) => union
+                        .variants
+                        .iter()
+                        .filter_map(|variant| variant.ty.clone())
+                        .collect(),
+
+                    NewtypeKind::Alias(_) => unreachable!("aliases are substituted"),
+                };
+
+                parts
+                    .into_iter()
+                    .try_for_each(|part| self.equatable_impl(part.substitute(&subst), checking))
+            }
+        }
+    }
+
+    /// Require values of `target` to be comparable with `<`, `>`, `<=` and `>=`.
+    pub fn orderable(&mut self, target: Type, span: Span) {
+        if let Some(subst_ty) = self.substitute_shallow(&target) {
+            return self.orderable(subst_ty, span);
+        }
+
+        match target {
+            Type::Var(var) => {
+                self.bounds_mut(var).ord = true;
+            }
+
+            Type::App(App {
+                kind: AppKind::Int | AppKind::Str,
+                ..
+            }) => {}
+
+            Type::App(ref app) if let Some(aliased) = self.substitute_alias(app) => {
+                self.orderable(aliased, span);
+            }
+
+            Type::App(_)
+                if self
+                    .lang
+                    .ord
+                    .is_some_and(|ord| self.find_impl(ord, &target).is_some()) => {}
+
+            Type::App(_) => {
+                let diagnostic = Diagnostic::error(format!(
+                    "type `{}` cannot be ordered",
+                    self.format_type(&target),
+                ))
+                .with_code(code::E0028)
+                .with_label(span, "arising from here")
+                .with_note("only `int`, `str` and types implementing `std::ord` can be ordered");
+
+                self.errors.push(diagnostic);
+            }
+        }
+    }
+
+    /// Require `target` to implement the trait `trid`.
+    pub fn implements(&mut self, target: Type, trid: Trid, span: Span) {
+        let target = self.unalias(target);
+
+        if let Type::Var(var) = target {
+            let bounds = self.bounds_mut(var);
+
+            if !bounds.traits.contains(&trid) {
+                bounds.traits.push(trid);
+            }
+
+            return;
+        }
+
+        match self.find_impl(trid, &target) {
+            // the impl may require more of the type, like its generics implementing traits
+            Some(r#impl) => {
+                let ty = self.instantiate(r#impl.ty.clone());
+                self.unify(target, ty, span);
+            }
+
+            // without an impl the runtime compares, hashes and formats values
+            // by their contents
+            None if self.lang.eq == Some(trid) => self.equatable(target, span),
+            None if self.lang.hash == Some(trid) || self.lang.format == Some(trid) => {}
+
+            None => {
+                let can_implement = self.can_implement(trid, &target);
+
+                let [ty] = &self.format_types(&[target])[..] else {
+                    unreachable!();
+                };
+
+                let name = &self[trid].name;
+
+                let diagnostic =
+                    Diagnostic::error(format!("type `{ty}` does not implement `{name}`"))
+                        .with_code(code::E0029)
+                        .with_label(span, "arising from here");
+
+                let diagnostic = match can_implement {
+                    true => diagnostic.with_help(format!("add an impl of `{name}` for `{ty}`")),
+                    false => diagnostic
+                        .with_help("wrap the value in a newtype and implement the trait for it"),
+                };
+
+                self.errors.push(diagnostic);
+            }
+        }
+    }
+
     pub fn instantiate(&mut self, mut ty: Type) -> Type {
         self.instantiate_impl(&mut ty, &mut HashMap::new());
         ty
     }
 
+    /// Instantiate `ty`, also returning the fresh variable of every variable
+    /// in it, so bounds the variables gain later can be given to the instance
+    /// with [`TypeContext::bound_instance`].
+    pub fn instantiate_with_vars(&mut self, mut ty: Type) -> (Type, HashMap<Var, Var>) {
+        let mut new_vars = HashMap::new();
+        self.instantiate_impl(&mut ty, &mut new_vars);
+        (ty, new_vars)
+    }
+
+    /// Give the fresh variables of an instance the bounds of the variables
+    /// they were instantiated from.
+    pub fn bound_instance(&mut self, new_vars: &HashMap<Var, Var>, span: Span) {
+        let mut new_vars = new_vars.iter().collect::<Vec<_>>();
+        new_vars.sort();
+
+        for (var, fresh) in new_vars {
+            let Some(bounds) = self.bounds.get(var).cloned() else {
+                continue;
+            };
+
+            if bounds.number {
+                self.number(Type::Var(*fresh), span);
+            }
+
+            if bounds.eq {
+                self.equatable(Type::Var(*fresh), span);
+            }
+
+            if bounds.ord {
+                self.orderable(Type::Var(*fresh), span);
+            }
+        }
+    }
+
     fn instantiate_impl(&mut self, ty: &mut Type, new_vars: &mut HashMap<Var, Var>) {
         *ty = self.substitute(ty.clone());
 
@@ -183,6 +504,9 @@ impl TypeContext {
                 if let Some(bounds) = self.bounds.get(var).cloned() {
                     let mut new_bounds = Bounds {
                         number: bounds.number,
+                        eq: bounds.eq,
+                        ord: bounds.ord,
+                        traits: bounds.traits,
                         ..Default::default()
                     };
 
@@ -225,35 +549,68 @@ impl TypeContext {
         }
     }
 
-    pub fn unify(&mut self, lhs: Type, rhs: Type, span: Span) {
-        if let Some(lhs) = self.substitute_shallow(&lhs) {
-            return self.unify(lhs, rhs, span);
-        } else if let Some(rhs) = self.substitute_shallow(&rhs) {
-            return self.unify(lhs, rhs, span);
+    /// Unify the type `found` with the type `expected`, reporting an error
+    /// arising from `span` if they differ.
+    pub fn unify(&mut self, found: Type, expected: Type, span: Span) {
+        let outermost = self.unifying.is_none();
+
+        if outermost {
+            self.unifying = Some((found.clone(), expected.clone()));
         }
 
-        if self.cache.contains(&(lhs.clone(), rhs.clone())) {
+        self.unify_impl(found, expected, span);
+
+        if outermost {
+            self.unifying = None;
+        }
+    }
+
+    fn unify_impl(&mut self, found: Type, expected: Type, span: Span) {
+        if let Some(found) = self.substitute_shallow(&found) {
+            return self.unify_impl(found, expected, span);
+        } else if let Some(expected) = self.substitute_shallow(&expected) {
+            return self.unify_impl(found, expected, span);
+        }
+
+        if self.cache.contains(&(found.clone(), expected.clone())) {
             return;
         }
 
-        self.cache.insert((lhs.clone(), rhs.clone()));
+        self.cache.insert((found.clone(), expected.clone()));
 
-        if lhs == rhs {
+        if found == expected {
             return;
         }
 
-        match (lhs, rhs) {
+        match (found, expected) {
             (Type::Var(var), ty) | (ty, Type::Var(var)) => self.unify_var_ty(var, ty, span),
-            (Type::App(lhs), Type::App(rhs)) => self.unify_app_app(lhs, rhs, span),
+            (Type::App(found), Type::App(expected)) => self.unify_app_app(found, expected, span),
         }
     }
 
     fn unify_var_ty(&mut self, var: Var, ty: Type, span: Span) {
+        // a variable cannot be a type containing itself, that type would be infinite
+        if self.occurs(var, &ty) {
+            return self.infinite(var, ty, span);
+        }
+
         if let Some(bounds) = self.bounds.get(&var).cloned() {
             if bounds.number {
                 self.number(ty.clone(), span);
             }
 
+            if bounds.eq {
+                self.equatable(ty.clone(), span);
+            }
+
+            if bounds.ord {
+                self.orderable(ty.clone(), span);
+            }
+
+            for trid in bounds.traits {
+                self.implements(ty.clone(), trid, span);
+            }
+
             for (field_name, field_ty) in bounds.fields {
                 self.field(ty.clone(), &field_name, field_ty, span);
             }
@@ -262,89 +619,154 @@ impl TypeContext {
         self.subst.insert(var, ty);
     }
 
-    fn unify_app_app(&mut self, lhs: App, rhs: App, span: Span) {
-        if let Some(lhs) = self.substitute_alias(&lhs) {
-            return self.unify(lhs, Type::App(rhs), span);
-        } else if let Some(rhs) = self.substitute_alias(&rhs) {
-            return self.unify(Type::App(lhs), rhs, span);
+    fn unify_app_app(&mut self, found: App, expected: App, span: Span) {
+        if let Some(found) = self.substitute_alias(&found) {
+            return self.unify_impl(found, Type::App(expected), span);
+        } else if let Some(expected) = self.substitute_alias(&expected) {
+            return self.unify_impl(Type::App(found), expected, span);
         }
 
-        match (lhs.kind, rhs.kind) {
+        match (found.kind, expected.kind) {
             (AppKind::Int, AppKind::Int)
             | (AppKind::Bool, AppKind::Bool)
             | (AppKind::Str, AppKind::Str)
             | (AppKind::Unit, AppKind::Unit) => {}
 
-            (AppKind::List(lhs_element), AppKind::List(rhs_element)) => {
-                self.unify(*lhs_element, *rhs_element, span);
+            (AppKind::List(found_element), AppKind::List(expected_element)) => {
+                self.unify_impl(*found_element, *expected_element, span);
             }
 
-            (AppKind::Tuple(lhs_fields), AppKind::Tuple(rhs_fields)) => {
-                if lhs_fields.len() != rhs_fields.len() {
-                    let diagnostic = Diagnostic::error(format!(
-                        "cannot unify tuple types with different lengths: `{}` and `{}`",
-                        lhs_fields.len(),
-                        rhs_fields.len()
-                    ))
-                    .with_label(span, "arising from here");
-
-                    self.errors.push(diagnostic);
-                    return;
+            (AppKind::Tuple(found_fields), AppKind::Tuple(expected_fields))
+                if found_fields.len() == expected_fields.len() =>
+            {
+                for (found_field, expected_field) in found_fields.into_iter().zip(expected_fields) {
+                    self.unify_impl(found_field, expected_field, span);
                 }
+            }
 
-                for (lhs_field, rhs_field) in lhs_fields.into_iter().zip(rhs_fields) {
-                    self.unify(lhs_field, rhs_field, span);
+            (
+                AppKind::Newtype(found_tid, found_generics),
+                AppKind::Newtype(expected_tid, expected_generics),
+            ) if found_tid == expected_tid => {
+                assert_eq!(found_generics.len(), expected_generics.len());
+
+                for (found_generic, expected_generic) in
+                    found_generics.into_iter().zip(expected_generics)
+                {
+                    self.unify_impl(found_generic, expected_generic, span);
                 }
             }
 
-            (AppKind::Newtype(lhs_tid, lhs_generics), AppKind::Newtype(rhs_tid, rhs_generics)) => {
-                if lhs_tid != rhs_tid {
-                    let message = format!(
-                        "cannot unify newtypes: `{}` and `{}`",
-                        self[lhs_tid].name, self[rhs_tid].name
-                    );
-                    let diagnostic =
-                        Diagnostic::error(message).with_label(span, "arising from here");
+            (
+                AppKind::Function(found_input, found_output),
+                AppKind::Function(expected_input, expected_output),
+            ) => {
+                self.unify_impl(*found_input, *expected_input, span);
+                self.unify_impl(*found_output, *expected_output, span);
+            }
 
-                    self.errors.push(diagnostic);
-                    return;
-                }
+            (found_kind, expected_kind) => {
+                let found = App {
+                    kind: found_kind,
+                    span: found.span,
+                };
 
-                assert_eq!(lhs_generics.len(), rhs_generics.len());
+                let expected = App {
+                    kind: expected_kind,
+                    span: expected.span,
+                };
 
-                for (lhs_generic, rhs_generic) in lhs_generics.into_iter().zip(rhs_generics) {
-                    self.unify(lhs_generic, rhs_generic, span);
-                }
+                self.mismatch(found, expected, span);
             }
+        }
+    }
 
-            (
-                AppKind::Function(lhs_input, lhs_output),
-                AppKind::Function(rhs_input, rhs_output),
-            ) => {
-                self.unify(*lhs_input, *rhs_input, span);
-                self.unify(*lhs_output, *rhs_output, span);
-            }
+    /// Whether `var` occurs in `ty`, following the substitution.
+    pub fn occurs(&self, var: Var, ty: &Type) -> bool {
+        if let Some(ty) = self.substitute_shallow(ty) {
+            return self.occurs(var, &ty);
+        }
 
-            (lhs_kind, rhs_kind) => {
-                let lhs_ty = self.format_type(&Type::App(App {
-                    kind: lhs_kind,
-                    span: lhs.span,
-                }));
+        match ty {
+            Type::Var(other) => *other == var,
 
-                let rhs_ty = self.format_type(&Type::App(App {
-                    kind: rhs_kind,
-                    span: rhs.span,
-                }));
+            Type::App(app) => match app.kind {
+                AppKind::Int | AppKind::Bool | AppKind::Str | AppKind::Unit => false,
 
-                let diagnostic =
-                    Diagnostic::error(format!("cannot unify types: `{lhs_ty}` and `{rhs_ty}`",))
-                        .with_label(span, "constraint arising from here")
-                        .with_label(lhs.span, format!("`{lhs_ty}` here"))
-                        .with_label(rhs.span, format!("`{rhs_ty}` here"));
+                AppKind::List(ref element) => self.occurs(var, element),
 
-                self.errors.push(diagnostic);
-            }
+                AppKind::Tuple(ref items) | AppKind::Newtype(_, ref items) => {
+                    items.iter().any(|item| self.occurs(var, item))
+                }
+
+                AppKind::Function(ref input, ref output) => {
+                    self.occurs(var, input) || self.occurs(var, output)
+                }
+            },
+        }
+    }
+
+    fn infinite(&mut self, var: Var, ty: Type, span: Span) {
+        let ty = self.substitute(ty);
+
+        // expand the type a couple of times to show how it recurses
+        let subst = HashMap::from([(var, ty.clone())]);
+        let expanded = ty.clone().substitute(&subst).substitute(&subst);
+
+        let types = self.format_types(&[Type::Var(var), ty, expanded]);
+        let [var, ty, expanded] = &types[..] else {
+            unreachable!();
+        };
+
+        let diagnostic = Diagnostic::error(format!("infinite type: `{var}` occurs in `{ty}`"))
+            .with_code(code::E0026)
+            .with_label(span, format!("`{var}` would have to be `{ty}`"))
+            .with_note(format!(
+                "expanding `{var}` gives `{expanded}`, and so on forever"
+            ));
+
+        self.errors.push(diagnostic);
+    }
+
+    fn mismatch(&mut self, found: App, expected: App, span: Span) {
+        let (found_span, expected_span) = (found.span, expected.span);
+        let (found, expected) = (Type::App(found), Type::App(expected));
+
+        let (outer_found, outer_expected) = self
+            .unifying
+            .clone()
+            .unwrap_or_else(|| (found.clone(), expected.clone()));
+
+        let types = self.format_types(&[found, expected, outer_found, outer_expected]);
+        let [found, expected, outer_found, outer_expected] = &types[..] else {
+            unreachable!();
+        };
+
+        let message = format!("expected `{outer_expected}`, found `{outer_found}`");
+
+        let mut diagnostic = Diagnostic::error(format!("mismatched types: {message}"))
+            .with_code(code::E0023)
+            .with_label(span, message);
+
+        if expected_span != span {
+            diagnostic = diagnostic.with_secondary_label(
+                expected_span,
+                format!("expected `{expected}` because of this"),
+            );
+        }
+
+        if found_span != span {
+            diagnostic =
+                diagnostic.with_secondary_label(found_span, format!("found `{found}` here"));
         }
+
+        if (found, expected) != (outer_found, outer_expected) {
+            diagnostic = diagnostic.with_note(format!(
+                "the mismatched parts are `{expected}` and `{found}`"
+            ));
+        }
+
+        self.errors.push(diagnostic);
     }
 
     fn substitute_alias(&self, app: &App) -> Option<Type> {
@@ -485,6 +907,29 @@ impl TypeContext {
         )
     }
 
+    /// Format `types` without quantifying over their variables, giving each
+    /// variable the same name in every type.
+    pub fn format_types(&self, types: &[Type]) -> Vec<String> {
+        let mut var_names = HashMap::new();
+
+        for ty in types {
+            let mut vars = HashSet::new();
+            self.enumerate_vars(ty, &mut vars);
+
+            for var in vars {
+                let index = var_names.len();
+                var_names
+                    .entry(var)
+                    .or_insert_with(|| Self::generate_var_name(index));
+            }
+        }
+
+        types
+            .iter()
+            .map(|ty| self.format_type_impl(ty, &var_names, 0))
+            .collect()
+    }
+
     fn format_bounds(&self, bounds: &Bounds, var_names: &HashMap<Var, String>) -> String {
         let mut parts = Vec::new();
 
@@ -492,6 +937,18 @@ impl TypeContext {
             parts.push("number".to_string());
         }
 
+        if bounds.eq {
+            parts.push("eq".to_string());
+        }
+
+        if bounds.ord {
+            parts.push("ord".to_string());
+        }
+
+        for &trid in &bounds.traits {
+            parts.push(self[trid].name.clone());
+        }
+
         for (name, field_ty) in &bounds.fields {
             let field_str = format!(
                 ".{} = {}",
@@ -622,6 +1079,20 @@ impl TypeContext {
     }
 }
 
+impl Index<Trid> for TypeContext {
+    type Output = Trait;
+
+    fn index(&self, trid: Trid) -> &Self::Output {
+        &self.traits[trid.index as usize]
+    }
+}
+
+impl IndexMut<Trid> for TypeContext {
+    fn index_mut(&mut self, trid: Trid) -> &mut Self::Output {
+        &mut self.traits[trid.index as usize]
+    }
+}
+
 impl Index<Tid> for TypeContext {
     type Output = Newtype;
 
@@ -639,6 +1110,9 @@ impl IndexMut<Tid> for TypeContext {
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Bounds {
     pub number: bool,
+    pub eq: bool,
+    pub ord: bool,
+    pub traits: Vec<Trid>,
     pub fields: HashMap<String, Type>,
 }
 
@@ -694,3 +1168,65 @@ pub struct Variant {
     pub name: String,
     pub ty: Option<Type>,
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Trid {
+    index: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trait {
+    pub name: String,
+    /// The type the trait is implemented for.
+    pub var: Var,
+    pub methods: Vec<Method>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Method {
+    pub name: String,
+    pub body: Bid,
+    pub ty: Type,
+    /// The generics of the method, other than the type of the trait.
+    pub generics: Vec<Var>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Impl {
+    pub r#trait: Trid,
+    pub ty: Type,
+    pub methods: HashMap<String, Bid>,
+    pub span: Span,
+}
+
+/// The traits used by built-in operations, if they are defined.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LangTraits {
+    pub eq: Option<Trid>,
+    pub ord: Option<Trid>,
+    pub hash: Option<Trid>,
+    pub format: Option<Trid>,
+}
+
+impl LangTraits {
+    pub fn contains(&self, trid: Trid) -> bool {
+        [self.eq, self.ord, self.hash, self.format].contains(&Some(trid))
+    }
+}
+
+/// Whether two types have the same outermost type constructor.
+fn same_head(a: &AppKind, b: &AppKind) -> bool {
+    match (a, b) {
+        (AppKind::Int, AppKind::Int)
+        | (AppKind::Str, AppKind::Str)
+        | (AppKind::Bool, AppKind::Bool)
+        | (AppKind::Unit, AppKind::Unit)
+        | (AppKind::List(_), AppKind::List(_))
+        | (AppKind::Function(..), AppKind::Function(..)) => true,
+
+        (AppKind::Tuple(a), AppKind::Tuple(b)) => a.len() == b.len(),
+        (AppKind::Newtype(a, _), AppKind::Newtype(b, _)) => a == b,
+
+        (_, _) => false,
+    }
+}
@@ -6,12 +6,15 @@ use std::{
     ops::{Index, IndexMut},
 };
 
+use crate::{attr::Attrs, diagnostic::Span};
+
 pub use context::*;
 pub use r#type::*;
 
 pub type Expr = super::Expr<Type>;
 pub type ExprKind = super::ExprKind<Type>;
 pub type Arm = super::Arm<Type>;
+pub type UnOp = super::UnOp;
 pub type BinOp = super::BinOp;
 pub type Pattern = super::Pattern<Type>;
 pub type PatternKind = super::PatternKind<Type>;
@@ -28,6 +31,8 @@ pub type Bid = super::Bid<Type>;
 pub struct Program {
     pub modules: Vec<Module>,
     pub bodies: Bodies,
+    pub calls: Vec<Call>,
+    pub attrs: HashMap<Bid, Attrs>,
     pub tcx: TypeContext,
     pub root: Mid,
 }
@@ -43,6 +48,8 @@ impl Program {
         Program {
             modules: vec![Module::default()],
             bodies: Bodies::default(),
+            calls: Vec::new(),
+            attrs: HashMap::new(),
             tcx: TypeContext::default(),
             root: Mid { index: 0 },
         }
@@ -96,11 +103,20 @@ impl IndexMut<Bid> for Program {
     }
 }
 
+/// A reference from the body of one function to another function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Call {
+    pub caller: Bid,
+    pub callee: Bid,
+    pub span: Span,
+}
+
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct Module {
     pub modules: HashMap<String, Mid>,
     pub bodies: HashMap<String, Bid>,
     pub newtypes: HashMap<String, Tid>,
+    pub traits: HashMap<String, Trid>,
     pub variants: HashMap<String, (Tid, String)>,
     pub imports: HashMap<String, Vec<String>>,
 }
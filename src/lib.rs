@@ -1,9 +1,10 @@
-#![allow(clippy::module_inception)]
+#![allow(clippy::collapsible_if, clippy::module_inception)]
 
 pub mod ast;
 pub mod attr;
 pub mod diagnostic;
 pub mod ir;
+pub mod lint;
 pub mod lower;
 pub mod lsp;
 pub mod lua;
@@ -0,0 +1,159 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    attr::Attrs,
+    diagnostic::{Diagnostic, Level},
+};
+
+/// A kind of warning that can be allowed, warned about or denied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariables,
+    UnusedImports,
+    DeadCode,
+    UnreachablePatterns,
+}
+
+impl Lint {
+    pub const ALL: &[Lint] = &[
+        Lint::UnusedVariables,
+        Lint::UnusedImports,
+        Lint::DeadCode,
+        Lint::UnreachablePatterns,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused-variables",
+            Lint::UnusedImports => "unused-imports",
+            Lint::DeadCode => "dead-code",
+            Lint::UnreachablePatterns => "unreachable-patterns",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+
+    pub fn default_level(self) -> LintLevel {
+        match self {
+            Lint::UnusedVariables => LintLevel::Warn,
+            Lint::UnusedImports => LintLevel::Warn,
+            Lint::DeadCode => LintLevel::Warn,
+            Lint::UnreachablePatterns => LintLevel::Warn,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+        }
+    }
+
+    /// The command line flag that sets this level.
+    pub fn flag(self) -> &'static str {
+        match self {
+            LintLevel::Allow => "-A",
+            LintLevel::Warn => "-W",
+            LintLevel::Deny => "-D",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// The registry of lint levels for a build.
+///
+/// Levels are taken from item attributes first, then from the command line,
+/// and finally from the default level of each lint.
+#[derive(Clone, Debug, Default)]
+pub struct Lints {
+    /// The level of each lint set on the command line, and the name it was
+    /// set by, which is either the lint or `warnings`.
+    levels: HashMap<Lint, (LintLevel, String)>,
+    noted: HashSet<Lint>,
+}
+
+impl Lints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the level of the lint called `name`, where `warnings` refers to
+    /// every lint.
+    pub fn set(&mut self, name: &str, level: LintLevel) -> Result<(), String> {
+        if name == "warnings" {
+            for &lint in Lint::ALL {
+                self.levels.insert(lint, (level, name.to_string()));
+            }
+
+            return Ok(());
+        }
+
+        let lint = Lint::from_name(name).ok_or_else(|| format!("unknown lint: `{name}`"))?;
+        self.levels.insert(lint, (level, name.to_string()));
+
+        Ok(())
+    }
+
+    /// Apply the level of the lint of `diagnostic`, returning `None` if the
+    /// lint is allowed.
+    ///
+    /// Diagnostics that are not lints are returned unchanged.
+    pub fn filter(&mut self, attrs: &Attrs, mut diagnostic: Diagnostic) -> Option<Diagnostic> {
+        let Some(lint) = diagnostic.lint else {
+            return Some(diagnostic);
+        };
+
+        let level = match attrs.lint_level(lint) {
+            Some(level) => level,
+            None => {
+                let level = self.levels.get(&lint);
+
+                // explain where the level comes from the first time the lint fires
+                if self.noted.insert(lint) {
+                    let note = match level {
+                        Some((level, name)) => {
+                            format!("`{} {name}` requested on the command line", level.flag())
+                        }
+                        None => format!(
+                            "`#[{}({})]` on by default",
+                            lint.default_level().name(),
+                            lint.name(),
+                        ),
+                    };
+
+                    diagnostic = diagnostic.with_note(note);
+                }
+
+                level.map_or(lint.default_level(), |(level, _)| *level)
+            }
+        };
+
+        diagnostic.level = match level {
+            LintLevel::Allow => return None,
+            LintLevel::Warn => Level::Warn,
+            LintLevel::Deny => Level::Error,
+        };
+
+        Some(diagnostic)
+    }
+}
@@ -0,0 +1,892 @@
+use std::{fmt, mem};
+
+use crate::{
+    diagnostic::{Diagnostic, Span, code},
+    ir::untyped as ir,
+    lint::Lint,
+};
+
+use super::{ExprLowerer, LowerError, Lowerer};
+
+/// The maximum number of missing patterns listed in a diagnostic.
+const MAX_WITNESSES: usize = 3;
+
+/// The arms of a `match`, checked once the types of the program are known.
+pub(super) struct Match {
+    pub(super) body: ir::Bid,
+    pub(super) span: Span,
+    pub(super) source: MatchSource,
+
+    /// The pattern of every arm, and whether the arm has a guard.
+    pub(super) arms: Vec<(ir::Pattern, bool)>,
+}
+
+/// Where the arms of a [`Match`] come from.
+///
+/// Patterns outside of `match` expressions have a single arm, which must be
+/// irrefutable.
+pub(super) enum MatchSource {
+    Match,
+
+    /// A `let` binding of the value at the span.
+    Let(Span),
+
+    Parameter,
+    Lambda,
+}
+
+impl ExprLowerer<'_, '_> {
+    /// Require `pattern` to match every value, which is checked once the types
+    /// of the program are known.
+    pub(super) fn irrefutable(&mut self, pattern: &ir::Pattern, source: MatchSource) {
+        self.lowerer.matches.push(Match {
+            body: self.body,
+            span: pattern.span,
+            source,
+            arms: vec![(pattern.clone(), false)],
+        });
+    }
+}
+
+impl Lowerer<'_> {
+    /// Check the exhaustiveness of every `match`.
+    ///
+    /// Patterns of mismatched types cannot be checked, so this must only be
+    /// done once the program is known to be well typed.
+    pub(super) fn exhaust_matches(&mut self) -> Result<(), LowerError> {
+        let mut result = Ok(());
+
+        for r#match in mem::take(&mut self.matches) {
+            result = result.and(self.exhaust(r#match));
+        }
+
+        result
+    }
+
+    /// Check that the arms of `r#match` cover every possible value, and warn
+    /// about arms that can never match because the arms before them already
+    /// cover them.
+    ///
+    /// A pattern is irrefutable when it alone covers every value.
+    ///
+    /// An arm with a guard may not match, so it covers nothing.
+    ///
+    /// This is the usefulness algorithm from "Warnings for pattern matching"
+    /// by Luc Maranget.
+    fn exhaust(&mut self, r#match: Match) -> Result<(), LowerError> {
+        let mut matrix = Matrix { rows: Vec::new() };
+
+        for (pattern, guarded) in &r#match.arms {
+            let row = MatrixRow::new(self.expand(pattern));
+
+            if !self.is_useful(&matrix, &row) {
+                let diagnostic = Diagnostic::warn("unreachable pattern")
+                    .with_label(pattern.span, "this arm can never match")
+                    .with_lint(Lint::UnreachablePatterns);
+
+                self.lint(r#match.body, diagnostic);
+            }
+
+            if !guarded {
+                matrix.rows.extend(row.alternatives());
+            }
+        }
+
+        let witnesses = self.missing(&matrix, 1);
+
+        if witnesses.is_empty() {
+            return Ok(());
+        }
+
+        let mut patterns = witnesses
+            .iter()
+            .take(MAX_WITNESSES)
+            .map(|witness| format!("`{}`", witness[0]))
+            .collect::<Vec<_>>();
+
+        let missing = match witnesses.len() {
+            1 => format!("pattern {} not covered", patterns[0]),
+            len if len > MAX_WITNESSES => {
+                let patterns = patterns.join(", ");
+                format!(
+                    "patterns {patterns} and {} more not covered",
+                    len - MAX_WITNESSES
+                )
+            }
+            _ => {
+                let last = patterns.pop().unwrap();
+                format!("patterns {} and {last} not covered", patterns.join(", "))
+            }
+        };
+
+        let refutable = |what: &str| {
+            Diagnostic::error(format!("refutable pattern in {what}"))
+                .with_code(code::E0016)
+                .with_label(r#match.span, missing.clone())
+                .with_help("use a `match` expression to handle every case")
+        };
+
+        let diagnostic = match r#match.source {
+            MatchSource::Match => Diagnostic::error(format!("non-exhaustive patterns: {missing}"))
+                .with_code(code::E0022)
+                .with_label(r#match.span, missing.clone())
+                .with_help("add arms for the missing patterns, or a wildcard arm `_ -> ...`"),
+
+            MatchSource::Let(value) => {
+                refutable("let binding").with_secondary_label(value, "value is here")
+            }
+
+            MatchSource::Parameter => refutable("function parameter"),
+            MatchSource::Lambda => refutable("lambda"),
+        };
+
+        self.emitter.emit(diagnostic);
+        Err(LowerError)
+    }
+
+    /// Whether `row` matches any value that no row of `matrix` matches.
+    fn is_useful(&self, matrix: &Matrix, row: &MatrixRow) -> bool {
+        let Some(pattern) = row.patterns.first() else {
+            return matrix.rows.is_empty();
+        };
+
+        if let ir::PatternKind::Or(_) = pattern.kind {
+            return row
+                .alternatives()
+                .iter()
+                .any(|row| self.is_useful(matrix, row));
+        }
+
+        if let Some(cons) = Cons::of(pattern) {
+            return matrix.split(cons).iter().any(|cons| {
+                self.is_useful(&matrix.specialize(cons), &row.specialize(cons).unwrap())
+            });
+        }
+
+        match self.complete_signature(matrix) {
+            Some(signature) => signature.iter().any(|cons| {
+                self.is_useful(&matrix.specialize(cons), &row.specialize(cons).unwrap())
+            }),
+            None => self.is_useful(&matrix.default(), &row.default().unwrap()),
+        }
+    }
+
+    /// Compute the values of `width` columns that no row of `matrix` matches.
+    fn missing(&self, matrix: &Matrix, width: usize) -> Vec<Vec<Witness>> {
+        if width == 0 {
+            return match matrix.rows.is_empty() {
+                true => vec![Vec::new()],
+                false => Vec::new(),
+            };
+        }
+
+        // list the missing values of every constructor, not just the first
+        // constructor that no row starts with, so that every missing pattern
+        // can be reported
+        let signature = self
+            .complete_signature(matrix)
+            .or_else(|| self.signature(matrix.first_head()?));
+
+        if let Some(signature) = signature {
+            let mut witnesses = Vec::new();
+
+            for cons in signature {
+                let specialized = matrix.specialize(&cons);
+
+                for witness in self.missing(&specialized, cons.arity() + width - 1) {
+                    let mut witness = Witness::apply(cons.clone(), witness);
+
+                    // name records after their type, which their constructor
+                    // does not know
+                    if let Cons::Record(tid, ref names) = cons {
+                        let Witness::Cons(_, fields) = witness.remove(0) else {
+                            unreachable!();
+                        };
+
+                        let name = self.ir.tcx[tid].name.clone();
+                        let fields = names.iter().cloned().zip(fields).collect();
+                        witness.insert(0, Witness::Record(name, fields));
+                    }
+
+                    witnesses.push(witness);
+                }
+            }
+
+            return witnesses;
+        }
+
+        let mut witnesses = self.missing(&matrix.default(), width - 1);
+
+        for witness in &mut witnesses {
+            witness.insert(0, Witness::Wildcard);
+        }
+
+        witnesses
+    }
+
+    /// The constructors of the first column of `matrix`, if every constructor
+    /// of its type is present.
+    fn complete_signature(&self, matrix: &Matrix) -> Option<Vec<Cons>> {
+        let head = matrix.first_head()?;
+        let heads = matrix.heads().collect::<Vec<_>>();
+
+        // integers have too many constructors to list, so split every integer
+        // into the ranges that are covered by the same patterns instead
+        if let Some(Cons::Range(_, _)) = Cons::of(head) {
+            let ranges = matrix.split(Cons::Range(i64::MIN, i64::MAX));
+            let covered = |range: &Cons| heads.iter().any(|head| head.covers(range));
+
+            return ranges.iter().all(covered).then_some(ranges);
+        }
+
+        let signature = self.signature(head)?;
+
+        match signature.iter().all(|cons| heads.contains(cons)) {
+            true => Some(signature),
+            false => None,
+        }
+    }
+
+    /// Expand variant patterns that leave out the value of their variant, like
+    /// `some` for `some _`, so that they match the constructor of the variant,
+    /// and record patterns to list every field of their record in order.
+    /// Remove `as` bindings, which match whatever their pattern matches.
+    fn expand(&self, pattern: &ir::Pattern) -> ir::Pattern {
+        let kind = match pattern.kind {
+            ir::PatternKind::Record(ref ty, ref fields) => {
+                let (_, record) = self.record(ty);
+
+                let fields = record.fields.iter().map(|field| {
+                    let pattern = match fields.iter().find(|(name, _)| *name == field.name) {
+                        Some((_, pattern)) => self.expand(pattern),
+                        None => ir::Pattern {
+                            kind: ir::PatternKind::Wildcard,
+                            span: pattern.span,
+                        },
+                    };
+
+                    (field.name.clone(), pattern)
+                });
+
+                ir::PatternKind::Record(ty.clone(), fields.collect())
+            }
+
+            ir::PatternKind::As(ref pattern, _) => return self.expand(pattern),
+
+            ir::PatternKind::Or(ref patterns) => ir::PatternKind::Or(
+                patterns
+                    .iter()
+                    .map(|pattern| self.expand(pattern))
+                    .collect(),
+            ),
+
+            ir::PatternKind::Tuple(ref items) => {
+                ir::PatternKind::Tuple(items.iter().map(|item| self.expand(item)).collect())
+            }
+
+            ir::PatternKind::ListCons(ref head, ref tail) => {
+                ir::PatternKind::ListCons(Box::new(self.expand(head)), Box::new(self.expand(tail)))
+            }
+
+            ir::PatternKind::Prefix(ref prefix, ref rest) => {
+                ir::PatternKind::Prefix(prefix.clone(), Box::new(self.expand(rest)))
+            }
+
+            ir::PatternKind::Variant(ref ty, ref name, ref inner) => {
+                let inner = match inner {
+                    Some(inner) => Some(Box::new(self.expand(inner))),
+                    None => {
+                        let union = self.union(ty);
+                        let variant = union.variants.iter().find(|v| v.name == *name);
+
+                        variant.and_then(|v| v.ty.as_ref()).map(|_| {
+                            Box::new(ir::Pattern {
+                                kind: ir::PatternKind::Wildcard,
+                                span: pattern.span,
+                            })
+                        })
+                    }
+                };
+
+                ir::PatternKind::Variant(ty.clone(), name.clone(), inner)
+            }
+
+            ref kind => kind.clone(),
+        };
+
+        ir::Pattern {
+            kind,
+            span: pattern.span,
+        }
+    }
+
+    fn record(&self, ty: &ir::Type) -> (ir::Tid, &ir::Record) {
+        let ir::Type::App(ir::App {
+            kind: ir::AppKind::Newtype(tid, _),
+            ..
+        }) = ty
+        else {
+            unreachable!();
+        };
+
+        let ir::NewtypeKind::Record(ref record) = self.ir.tcx[*tid].kind else {
+            unreachable!();
+        };
+
+        (*tid, record)
+    }
+
+    fn union(&self, ty: &ir::Type) -> &ir::Union {
+        let ir::Type::App(ir::App {
+            kind: ir::AppKind::Newtype(tid, _),
+            ..
+        }) = ty
+        else {
+            unreachable!();
+        };
+
+        let ir::NewtypeKind::Union(ref union) = self.ir.tcx[*tid].kind else {
+            unreachable!();
+        };
+
+        union
+    }
+
+    /// Every constructor of the type of `pattern`, or `None` if there are
+    /// infinitely many.
+    fn signature(&self, pattern: &ir::Pattern) -> Option<Vec<Cons>> {
+        match pattern.kind {
+            ir::PatternKind::Bool(_) => Some(vec![Cons::Bool(true), Cons::Bool(false)]),
+            ir::PatternKind::Int(_)
+            | ir::PatternKind::Range(_, _)
+            | ir::PatternKind::String(_)
+            | ir::PatternKind::Prefix(_, _) => None,
+            ir::PatternKind::ListEmpty | ir::PatternKind::ListCons(_, _) => {
+                Some(vec![Cons::List(false), Cons::List(true)])
+            }
+            ir::PatternKind::Tuple(ref items) => Some(vec![Cons::Tuple(items.len())]),
+
+            ir::PatternKind::Record(ref ty, _) => {
+                let (tid, record) = self.record(ty);
+                let names = record.fields.iter().map(|field| field.name.clone());
+
+                Some(vec![Cons::Record(tid, names.collect())])
+            }
+
+            ir::PatternKind::Variant(ref ty, _, _) => {
+                let signature = self
+                    .union(ty)
+                    .variants
+                    .iter()
+                    .map(|variant| Cons::Variant(variant.name.clone(), variant.ty.is_some()))
+                    .collect();
+
+                Some(signature)
+            }
+
+            ir::PatternKind::Wildcard
+            | ir::PatternKind::Binding(_)
+            | ir::PatternKind::Or(_)
+            | ir::PatternKind::As(_, _) => {
+                unreachable!();
+            }
+        }
+    }
+}
+
+fn is_wildcard(pattern: &ir::Pattern) -> bool {
+    matches!(
+        pattern.kind,
+        ir::PatternKind::Wildcard | ir::PatternKind::Binding(_)
+    )
+}
+
+#[derive(Clone, Debug)]
+struct MatrixRow {
+    patterns: Vec<ir::Pattern>,
+}
+
+impl MatrixRow {
+    fn new(pattern: ir::Pattern) -> Self {
+        Self {
+            patterns: vec![pattern],
+        }
+    }
+
+    /// Split a row starting with an or-pattern into a row per alternative.
+    fn alternatives(&self) -> Vec<Self> {
+        let Some(ir::PatternKind::Or(patterns)) = self.patterns.first().map(|p| &p.kind) else {
+            return vec![self.clone()];
+        };
+
+        let mut rows = Vec::new();
+
+        for pattern in patterns {
+            let mut row = self.clone();
+            row.patterns[0] = pattern.clone();
+            rows.extend(row.alternatives());
+        }
+
+        rows
+    }
+
+    fn specialize(&self, cons: &Cons) -> Option<Self> {
+        let pattern = &self.patterns[0];
+        let mut patterns = cons.specialize(pattern)?;
+        patterns.extend(self.patterns.iter().skip(1).cloned());
+        Some(Self { patterns })
+    }
+
+    fn default(&self) -> Option<Self> {
+        match is_wildcard(&self.patterns[0]) {
+            true => Some(Self {
+                patterns: self.patterns[1..].to_vec(),
+            }),
+            false => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Matrix {
+    rows: Vec<MatrixRow>,
+}
+
+impl Matrix {
+    /// The first pattern of the first column that is not a wildcard.
+    fn first_head(&self) -> Option<&ir::Pattern> {
+        self.rows
+            .iter()
+            .map(|row| &row.patterns[0])
+            .find(|pattern| !is_wildcard(pattern))
+    }
+
+    /// The constructors of the first column.
+    fn heads(&self) -> impl Iterator<Item = Cons> + '_ {
+        self.rows
+            .iter()
+            .filter_map(|row| Cons::of(&row.patterns[0]))
+    }
+
+    /// Split `cons` into ranges that are either entirely inside or entirely
+    /// outside of every range in the first column.
+    fn split(&self, cons: Cons) -> Vec<Cons> {
+        let Cons::Range(lower, upper) = cons else {
+            return vec![cons];
+        };
+
+        let mut bounds = vec![lower];
+
+        for head in self.heads() {
+            if let Cons::Range(start, end) = head {
+                bounds.push(start);
+                bounds.extend(end.checked_add(1));
+            }
+        }
+
+        bounds.retain(|&bound| lower <= bound && bound <= upper);
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let ends = bounds.iter().skip(1).map(|bound| bound - 1).chain([upper]);
+        let ranges = bounds.iter().zip(ends);
+
+        ranges
+            .map(|(&start, end)| Cons::Range(start, end))
+            .collect()
+    }
+
+    fn specialize(&self, cons: &Cons) -> Self {
+        let rows = self.rows.iter().filter_map(|row| row.specialize(cons));
+        let rows = rows.flat_map(|row| row.alternatives());
+
+        Self {
+            rows: rows.collect(),
+        }
+    }
+
+    fn default(&self) -> Self {
+        let rows = self.rows.iter().filter_map(MatrixRow::default);
+        let rows = rows.flat_map(|row| row.alternatives());
+
+        Self {
+            rows: rows.collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Cons {
+    String(String),
+    Prefix(String),
+    Range(i64, i64),
+    Bool(bool),
+    List(bool),
+    Tuple(usize),
+    Record(ir::Tid, Vec<String>),
+    Variant(String, bool),
+}
+
+impl Cons {
+    /// The constructor `pattern` matches, or `None` if it matches anything.
+    fn of(pattern: &ir::Pattern) -> Option<Self> {
+        Some(match pattern.kind {
+            ir::PatternKind::String(ref value) => Cons::String(value.clone()),
+            ir::PatternKind::Prefix(ref prefix, _) => Cons::Prefix(prefix.clone()),
+            ir::PatternKind::Int(value) => Cons::Range(value, value),
+            ir::PatternKind::Range(lower, upper) => Cons::Range(lower, upper),
+            ir::PatternKind::Bool(value) => Cons::Bool(value),
+            ir::PatternKind::ListEmpty => Cons::List(false),
+            ir::PatternKind::ListCons(_, _) => Cons::List(true),
+            ir::PatternKind::Tuple(ref items) => Cons::Tuple(items.len()),
+            ir::PatternKind::Record(ref ty, ref fields) => {
+                let ir::Type::App(ir::App {
+                    kind: ir::AppKind::Newtype(tid, _),
+                    ..
+                }) = *ty
+                else {
+                    unreachable!();
+                };
+
+                Cons::Record(tid, fields.iter().map(|(name, _)| name.clone()).collect())
+            }
+            ir::PatternKind::Variant(_, ref name, ref inner) => {
+                Cons::Variant(name.clone(), inner.is_some())
+            }
+            ir::PatternKind::Wildcard | ir::PatternKind::Binding(_) => return None,
+
+            // or-patterns are split into rows, and `as` bindings are expanded
+            ir::PatternKind::Or(_) | ir::PatternKind::As(_, _) => unreachable!(),
+        })
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Cons::String(_)
+            | Cons::Range(_, _)
+            | Cons::Bool(_)
+            | Cons::List(false)
+            | Cons::Variant(_, false) => 0,
+
+            Cons::List(true) => 2,
+            Cons::Prefix(_) => 1,
+            Cons::Tuple(len) => *len,
+            Cons::Record(_, names) => names.len(),
+            Cons::Variant(_, true) => 1,
+        }
+    }
+
+    /// Whether every value of `other` is a value of this constructor.
+    fn covers(&self, other: &Cons) -> bool {
+        match (self, other) {
+            (Cons::Range(lower, upper), Cons::Range(start, end)) => lower <= start && end <= upper,
+            _ => self == other,
+        }
+    }
+
+    /// The sub-patterns of `pattern` if it matches this constructor.
+    fn specialize(&self, pattern: &ir::Pattern) -> Option<Vec<ir::Pattern>> {
+        if is_wildcard(pattern) {
+            let wildcard = ir::Pattern {
+                kind: ir::PatternKind::Wildcard,
+                span: pattern.span,
+            };
+
+            return Some(vec![wildcard; self.arity()]);
+        }
+
+        if !Cons::of(pattern).is_some_and(|cons| cons.covers(self)) {
+            return None;
+        }
+
+        Some(match pattern.kind {
+            ir::PatternKind::Tuple(ref items) => items.clone(),
+            ir::PatternKind::Record(_, ref fields) => {
+                fields.iter().map(|(_, pattern)| pattern.clone()).collect()
+            }
+            ir::PatternKind::ListCons(ref head, ref tail) => {
+                vec![head.as_ref().clone(), tail.as_ref().clone()]
+            }
+            ir::PatternKind::Variant(_, _, Some(ref inner)) => vec![inner.as_ref().clone()],
+            ir::PatternKind::Prefix(_, ref rest) => vec![rest.as_ref().clone()],
+            _ => Vec::new(),
+        })
+    }
+}
+
+/// A pattern describing values that are not matched.
+#[derive(Clone, Debug)]
+enum Witness {
+    Wildcard,
+    Cons(Cons, Vec<Witness>),
+    Record(String, Vec<(String, Witness)>),
+}
+
+impl Witness {
+    /// Replace the first `cons.arity()` witnesses with `cons` applied to them.
+    fn apply(cons: Cons, mut witnesses: Vec<Witness>) -> Vec<Witness> {
+        let fields = witnesses.drain(..cons.arity()).collect();
+        witnesses.insert(0, Witness::Cons(cons, fields));
+        witnesses
+    }
+}
+
+impl fmt::Display for Witness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (cons, fields) = match self {
+            Witness::Wildcard => return write!(f, "_"),
+            Witness::Cons(cons, fields) => (cons, fields),
+
+            // leave out fields that can have any value
+            Witness::Record(name, fields) => {
+                let fields = fields
+                    .iter()
+                    .filter(|(_, field)| !matches!(field, Witness::Wildcard))
+                    .map(|(name, field)| format!("{name}: {field}; "));
+
+                return write!(f, "{name} {{ {}.. }}", fields.collect::<String>());
+            }
+        };
+
+        match cons {
+            Cons::String(value) => write!(f, "{value:?}"),
+            Cons::Prefix(prefix) => write!(f, "{prefix:?} ++ {}", fields[0]),
+            Cons::Range(lower, upper) if lower == upper => write!(f, "{lower}"),
+            Cons::Range(lower, upper) => write!(f, "{lower}..={upper}"),
+            Cons::Bool(value) => write!(f, "{value}"),
+
+            Cons::Tuple(_) => {
+                let fields = fields.iter().map(ToString::to_string);
+                write!(f, "({})", fields.collect::<Vec<_>>().join(", "))
+            }
+
+            // records are named by `missing`, see `Witness::Record`
+            Cons::Record(_, _) => unreachable!(),
+
+            Cons::Variant(name, false) => write!(f, "{name}"),
+            Cons::Variant(name, true) => match &fields[0] {
+                field @ Witness::Cons(Cons::Variant(_, true), _) => write!(f, "{name} ({field})"),
+                field => write!(f, "{name} {field}"),
+            },
+
+            Cons::List(false) => write!(f, "[]"),
+            Cons::List(true) => {
+                let mut items = vec![fields[0].to_string()];
+                let mut tail = &fields[1];
+
+                while let Witness::Cons(Cons::List(true), fields) = tail {
+                    items.push(fields[0].to_string());
+                    tail = &fields[1];
+                }
+
+                match tail {
+                    Witness::Cons(Cons::List(false), _) => write!(f, "[{}]", items.join("; ")),
+                    _ => write!(f, "[{}; ..]", items.join("; ")),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cons, Matrix, MatrixRow};
+    use crate::{
+        ast,
+        diagnostic::{Diagnostic, Source, Sources, Span},
+        ir::untyped as ir,
+        lower::Lowerer,
+        parse,
+    };
+
+    /// Lower `source`, returning the messages of the diagnostics about
+    /// patterns.
+    fn check(source: &str) -> Vec<String> {
+        let mut sources = Sources::new();
+        let sid = sources.add(Source {
+            path: "test.ike".into(),
+            content: source.to_string(),
+        });
+
+        let mut diagnostics = Vec::<Diagnostic>::new();
+
+        let mut tokens = parse::tokenize(&sources[sid].content, sid, &mut diagnostics).unwrap();
+        let file = parse::parse_file(&mut tokens, &mut diagnostics).unwrap();
+
+        let mut module = ast::Module::new();
+        module.files.insert(String::from("test"), file);
+
+        let mut lowerer = Lowerer::new(&mut diagnostics);
+        let _ = lowerer.add_module(&["test"], &module);
+        let _ = lowerer.finish();
+
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .filter(|message| message.contains("pattern"))
+            .collect()
+    }
+
+    fn range(lower: i64, upper: i64) -> MatrixRow {
+        MatrixRow::new(ir::Pattern {
+            kind: ir::PatternKind::Range(lower, upper),
+            span: Span::dummy(),
+        })
+    }
+
+    #[test]
+    fn missing_long_list() {
+        let source = "
+fn f : [int] -> int
+fn f xs {
+  match xs {
+    []  -> 0
+    [_] -> 1
+  }
+}
+";
+
+        assert_eq!(
+            check(source),
+            ["non-exhaustive patterns: pattern `[_; _; ..]` not covered"],
+        );
+    }
+
+    #[test]
+    fn missing_nested_variant() {
+        let source = "
+type opt 'a = some 'a | none
+
+fn f : opt (opt int, bool) -> int
+fn f o {
+  match o {
+    none          -> 0
+    some (none, _) -> 1
+  }
+}
+";
+
+        assert_eq!(
+            check(source),
+            ["non-exhaustive patterns: pattern `some (some _, _)` not covered"],
+        );
+    }
+
+    #[test]
+    fn unreachable_overlapping_range() {
+        let source = "
+fn f : int -> int
+fn f n {
+  match n {
+    0..=5 -> 0
+    3..=4 -> 1
+    _     -> 2
+  }
+}
+";
+
+        assert_eq!(check(source), ["unreachable pattern"]);
+    }
+
+    // integers are only covered by a wildcard or ranges covering all of them
+    #[test]
+    fn missing_between_ranges() {
+        let source = "
+fn f : int -> int
+fn f n {
+  match n {
+    -10..=-1 -> 0
+    1..=10   -> 1
+    _ if n > 10 -> 2
+  }
+}
+";
+
+        assert_eq!(
+            check(source),
+            ["non-exhaustive patterns: pattern `_` not covered"],
+        );
+    }
+
+    #[test]
+    fn guarded_arm_covers_nothing() {
+        let source = "
+fn f : bool -> bool -> int
+fn f a b {
+  match a {
+    true if b -> 0
+    false     -> 1
+  }
+}
+";
+
+        assert_eq!(
+            check(source),
+            ["non-exhaustive patterns: pattern `true` not covered"],
+        );
+    }
+
+    #[test]
+    fn exhaustive() {
+        let source = "
+type opt 'a = some 'a | none
+
+fn f : opt [int] -> int
+fn f o {
+  match o {
+    none              -> 0
+    some []           -> 1
+    some [0..=9; ..]  -> 2
+    some [_; ..]      -> 3
+  }
+}
+";
+
+        assert!(check(source).is_empty());
+    }
+
+    #[test]
+    fn full_range_is_exhaustive() {
+        let mut diagnostics = Vec::<Diagnostic>::new();
+        let lowerer = Lowerer::new(&mut diagnostics);
+
+        let matrix = Matrix {
+            rows: vec![range(i64::MIN, i64::MAX)],
+        };
+
+        assert!(lowerer.missing(&matrix, 1).is_empty());
+
+        let matrix = Matrix {
+            rows: vec![range(i64::MIN, -1), range(0, 0), range(1, i64::MAX)],
+        };
+
+        assert!(lowerer.missing(&matrix, 1).is_empty());
+    }
+
+    #[test]
+    fn split_overlapping_ranges() {
+        let matrix = Matrix {
+            rows: vec![range(0, 5), range(3, 4)],
+        };
+
+        assert_eq!(
+            matrix.split(Cons::Range(i64::MIN, i64::MAX)),
+            [
+                Cons::Range(i64::MIN, -1),
+                Cons::Range(0, 2),
+                Cons::Range(3, 4),
+                Cons::Range(5, 5),
+                Cons::Range(6, i64::MAX),
+            ],
+        );
+
+        let matrix = Matrix {
+            rows: vec![range(i64::MIN, i64::MAX)],
+        };
+
+        assert_eq!(
+            matrix.split(Cons::Range(i64::MIN, i64::MAX)),
+            [Cons::Range(i64::MIN, i64::MAX)],
+        );
+    }
+}
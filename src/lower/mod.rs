@@ -1,4 +1,7 @@
+mod exhaust;
 mod pattern;
+mod scc;
+mod unused;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -9,10 +12,16 @@ use std::{
 
 use crate::{
     ast,
-    diagnostic::{Diagnostic, Emitter, Span},
+    attr::Attrs,
+    diagnostic::{Diagnostic, Emitter, Span, code, suggest},
     ir::untyped as ir,
+    lint::{Lint, LintLevel, Lints},
 };
 
+use scc::Components;
+
+pub use unused::lint_unreachable;
+
 #[derive(Clone, Debug)]
 pub struct LowerError;
 
@@ -40,15 +49,46 @@ struct Function {
     module: ir::Mid,
 }
 
+struct Trait {
+    ast: ast::Trait,
+    module: ir::Mid,
+    bodies: Vec<ir::Bid>,
+}
+
+struct Hole {
+    name: Option<String>,
+    span: Span,
+    ty: ir::Type,
+    locals: Vec<(String, ir::Type)>,
+}
+
+/// An ascribed function instantiated within its own component, before the
+/// bodies of the component have given its type all of its bounds.
+struct Instance {
+    bid: ir::Bid,
+    vars: HashMap<ir::Var, ir::Var>,
+    span: Span,
+}
+
 pub struct Lowerer<'a> {
     emitter: &'a mut dyn Emitter,
     ir: ir::Program,
 
-    call_graph: HashMap<ir::Bid, HashSet<ir::Bid>>,
+    components: Components,
+    ascribed: HashSet<ir::Bid>,
+    instances: Vec<Instance>,
     externs: HashMap<ir::Bid, Extern>,
     newtypes: HashMap<ir::Tid, Newtype>,
     functions: HashMap<ir::Bid, Function>,
+    traits: HashMap<ir::Trid, Trait>,
+    impls: Vec<(ir::Mid, ast::Impl)>,
     ascriptions: Vec<(ir::Mid, ast::Ascription)>,
+    holes: Vec<Hole>,
+    matches: Vec<exhaust::Match>,
+
+    imports: Vec<(ir::Mid, String, Span, Attrs)>,
+    used: HashSet<(ir::Mid, String)>,
+    lints: Lints,
 }
 
 impl<'a> Lowerer<'a> {
@@ -57,14 +97,29 @@ impl<'a> Lowerer<'a> {
             emitter,
             ir: ir::Program::default(),
 
-            call_graph: HashMap::new(),
+            components: Components::default(),
+            ascribed: HashSet::new(),
+            instances: Vec::new(),
             externs: HashMap::new(),
             newtypes: HashMap::new(),
             functions: HashMap::new(),
+            traits: HashMap::new(),
+            impls: Vec::new(),
             ascriptions: Vec::new(),
+            holes: Vec::new(),
+            matches: Vec::new(),
+
+            imports: Vec::new(),
+            used: HashSet::new(),
+            lints: Lints::new(),
         }
     }
 
+    pub fn with_lints(mut self, lints: Lints) -> Self {
+        self.lints = lints;
+        self
+    }
+
     pub fn emitter(&mut self) -> &mut dyn Emitter {
         self.emitter
     }
@@ -122,13 +177,17 @@ impl<'a> Lowerer<'a> {
         let module = self.create_module(path.iter().copied());
 
         for item in ast.items {
+            self.check_attrs(item.attrs());
+
             match item {
                 ast::Item::Import(ast) => {
                     let name = ast.path.name().to_string();
                     (self.ir[module].imports).insert(
-                        name, //
+                        name.clone(), //
                         ast.path.segments().map(String::from).collect(),
                     );
+
+                    self.imports.push((module, name, ast.path.span, ast.attrs));
                 }
 
                 ast::Item::Newtype(ast) => {
@@ -181,6 +240,7 @@ impl<'a> Lowerer<'a> {
                                     "duplicate variant '{}' in module '{}'",
                                     name, ast.name
                                 ))
+                                .with_code(code::E0004)
                                 .with_label(ast.span, "found here");
 
                                 self.emitter.emit(diagnostic);
@@ -189,6 +249,7 @@ impl<'a> Lowerer<'a> {
 
                             let body = ir::Body {
                                 name: Self::create_name(path, name.segments()),
+                                span: variant.span,
                                 locals: ir::Locals::default(),
                                 inputs: Vec::new(),
                                 expr: None,
@@ -206,6 +267,7 @@ impl<'a> Lowerer<'a> {
                                     "duplicate body '{}' in module '{}'",
                                     name, ast.name
                                 ))
+                                .with_code(code::E0004)
                                 .with_label(ast.span, "found here");
 
                                 self.emitter.emit(diagnostic);
@@ -224,6 +286,7 @@ impl<'a> Lowerer<'a> {
                             "duplicate newtype '{}' in module '{}'",
                             ast.name, ast.name
                         ))
+                        .with_code(code::E0004)
                         .with_label(ast.span, "found here");
 
                         self.emitter.emit(diagnostic);
@@ -242,6 +305,7 @@ impl<'a> Lowerer<'a> {
                 ast::Item::Function(ast) => {
                     let body = ir::Body {
                         name: Self::create_name(path, ast.name.segments()),
+                        span: ast.name.span,
                         locals: ir::Locals::default(),
                         inputs: Vec::new(),
                         expr: None,
@@ -261,18 +325,21 @@ impl<'a> Lowerer<'a> {
                             "duplicate function '{}' in module '{}'",
                             ast.name, ast.name
                         ))
+                        .with_code(code::E0004)
                         .with_label(ast.name.span, "found here");
 
                         self.emitter.emit(diagnostic);
                         return Err(LowerError);
                     }
 
+                    self.ir.attrs.insert(bid, ast.attrs.clone());
                     self.functions.insert(bid, Function { ast, module });
                 }
 
                 ast::Item::Extern(ast) => {
                     let body = ir::Body {
                         name: Self::create_name(path, ast.name.segments()),
+                        span: ast.name.span,
                         locals: ir::Locals::default(),
                         inputs: Vec::new(),
                         expr: None,
@@ -290,24 +357,145 @@ impl<'a> Lowerer<'a> {
                             "duplicate extern '{}' in module '{}'",
                             ast.name, ast.name
                         ))
+                        .with_code(code::E0004)
                         .with_label(ast.name.span, "found here");
 
                         self.emitter.emit(diagnostic);
                         return Err(LowerError);
                     }
 
+                    self.ir.attrs.insert(bid, ast.attrs.clone());
                     self.externs.insert(bid, Extern { ast, module });
                 }
 
                 ast::Item::Ascription(ast) => {
                     self.ascriptions.push((module, ast));
                 }
+
+                ast::Item::Trait(ast) => {
+                    let r#trait = ir::Trait {
+                        name: Self::create_name(path, ast.name.segments()),
+                        var: ir::Var::fresh(ast.generic.1),
+                        methods: Vec::new(),
+                    };
+
+                    let trid = self.ir.tcx.push_trait(r#trait);
+
+                    let submodule = self.create_module_from(module, ast.name.modules());
+                    let existing = (self.ir[submodule].traits).insert(
+                        ast.name.name().to_string(), //
+                        trid,
+                    );
+
+                    if existing.is_some() {
+                        let diagnostic = Diagnostic::error(format!(
+                            "duplicate trait '{}' in module '{}'",
+                            ast.name.name(),
+                            Self::create_name(path, ast.name.modules()),
+                        ))
+                        .with_code(code::E0004)
+                        .with_label(ast.name.span, "found here");
+
+                        self.emitter.emit(diagnostic);
+                        return Err(LowerError);
+                    }
+
+                    let mut bodies = Vec::new();
+
+                    for method in &ast.methods {
+                        let body = ir::Body {
+                            name: Self::create_name(path, method.name.segments()),
+                            span: method.name.span,
+                            locals: ir::Locals::default(),
+                            inputs: Vec::new(),
+                            expr: None,
+                            ty: ir::Type::infer(method.span),
+                        };
+
+                        let bid = self.ir.bodies.push(body);
+
+                        let submodule = self.create_module_from(module, method.name.modules());
+                        let existing = (self.ir[submodule].bodies).insert(
+                            method.name.name().to_string(), //
+                            bid,
+                        );
+
+                        if existing.is_some() {
+                            let diagnostic = Diagnostic::error(format!(
+                                "duplicate function '{}' in module '{}'",
+                                method.name.name(),
+                                Self::create_name(path, method.name.modules()),
+                            ))
+                            .with_code(code::E0004)
+                            .with_label(method.name.span, "found here");
+
+                            self.emitter.emit(diagnostic);
+                            return Err(LowerError);
+                        }
+
+                        self.ir.attrs.insert(bid, ast.attrs.clone());
+                        bodies.push(bid);
+                    }
+
+                    self.traits.insert(
+                        trid,
+                        Trait {
+                            ast,
+                            module,
+                            bodies,
+                        },
+                    );
+                }
+
+                ast::Item::Impl(ast) => {
+                    self.impls.push((module, ast));
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Emit the lint `diagnostic` at the level set for the body `bid`.
+    fn lint(&mut self, bid: ir::Bid, diagnostic: Diagnostic) {
+        let default = Attrs::default();
+        let attrs = self.ir.attrs.get(&bid).unwrap_or(&default);
+
+        if let Some(diagnostic) = self.lints.filter(attrs, diagnostic) {
+            self.emitter.emit(diagnostic);
+        }
+    }
+
+    fn check_attrs(&mut self, attrs: &Attrs) {
+        for attr in &attrs.attrs {
+            if LintLevel::from_name(&attr.name).is_none() {
+                let mut diagnostic =
+                    Diagnostic::warn(format!("unknown attribute: `{}`", attr.name))
+                        .with_label(attr.span, "found here");
+
+                if let Some(suggestion) = suggest(&attr.name, ["allow", "warn", "deny"]) {
+                    diagnostic = diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+                }
+
+                self.emitter.emit(diagnostic);
+                continue;
+            }
+
+            if attr.value != "warnings" && Lint::from_name(&attr.value).is_none() {
+                let mut diagnostic = Diagnostic::warn(format!("unknown lint: `{}`", attr.value))
+                    .with_label(attr.span, "found here");
+
+                let names = Lint::ALL.iter().map(|lint| lint.name());
+
+                if let Some(suggestion) = suggest(&attr.value, names) {
+                    diagnostic = diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+                }
+
+                self.emitter.emit(diagnostic);
+            }
+        }
+    }
+
     fn lower_ascriptions(&mut self) -> Result<(), LowerError> {
         for (module, ascription) in mem::take(&mut self.ascriptions) {
             let mut generics = Vec::new();
@@ -327,6 +515,7 @@ impl<'a> Lowerer<'a> {
             let Some(&bid) = self.ir[module].bodies.get(ascription.name.name()) else {
                 let diagnostic =
                     Diagnostic::error(format!("function `{}` not found", ascription.name))
+                        .with_code(code::E0005)
                         .with_label(ascription.span, "for ascription found here");
 
                 self.emitter.emit(diagnostic);
@@ -337,25 +526,113 @@ impl<'a> Lowerer<'a> {
             let ty = self.ir[bid].ty.clone();
 
             self.ir.tcx.unify(ty, expected, ascription.span);
+            self.ascribed.insert(bid);
+
+            let attrs = self.ir.attrs.entry(bid).or_default();
+            attrs.attrs.extend(ascription.attrs.attrs);
         }
 
         Ok(())
     }
 
-    pub fn finish(mut self) -> Result<ir::Program, LowerError> {
+    /// Lower the added modules, returning the program even when parts of it
+    /// could not be lowered.
+    pub fn finish(mut self) -> (ir::Program, Result<(), LowerError>) {
+        let result = self.lower_program();
+        (self.ir, result)
+    }
+
+    fn lower_program(&mut self) -> Result<(), LowerError> {
         self.import_root_modules();
         self.resolve_imports()?;
         self.lower_newtypes()?;
+        self.lower_traits()?;
+        self.lower_impls()?;
         self.lower_externs()?;
         self.lower_ascriptions()?;
 
+        // impls are lowered first, so the requirements they place on their
+        // types are known wherever the impls are used
+        let impls = self.ir.tcx.impls().iter();
+        let bodies: Vec<_> = impls
+            .flat_map(|r#impl| r#impl.methods.values())
+            .copied()
+            .collect();
+
+        // every function is lowered even after an error, so that as much of
+        // the program as possible is available
+        let mut functions = Ok(());
+
+        for bid in bodies {
+            functions = functions.and(self.lower_function(bid));
+        }
+
         while let Some(bid) = self.functions.keys().next().copied() {
-            self.lower_function(bid)?;
+            functions = functions.and(self.lower_function(bid));
         }
 
-        self.ir.tcx.finish(self.emitter).map_err(|_| LowerError)?;
+        functions?;
+
+        self.lint_unused_imports();
+        self.lint_unused_locals();
+
+        // holes are reported once every constraint is solved, so their types
+        // are as complete as they can be
+        let types = self.ir.tcx.finish(self.emitter);
+        let holes = self.report_holes();
 
-        Ok(self.ir)
+        // patterns can only be checked for exhaustiveness once they are known
+        // to have the types they are matched against
+        let matches = match types {
+            Ok(()) => self.exhaust_matches(),
+            Err(_) => Ok(()),
+        };
+
+        if holes.is_err() || types.is_err() || matches.is_err() {
+            return Err(LowerError);
+        }
+
+        Ok(())
+    }
+
+    /// Report the type of every hole and the locals around it.
+    fn report_holes(&mut self) -> Result<(), LowerError> {
+        let holes = mem::take(&mut self.holes);
+
+        if holes.is_empty() {
+            return Ok(());
+        }
+
+        for hole in holes {
+            let mut types = vec![hole.ty];
+            types.extend(hole.locals.iter().map(|(_, ty)| ty.clone()));
+
+            let types = self.ir.tcx.format_types(&types);
+
+            let label = match hole.name {
+                Some(name) => format!("`?{name}` has type `{}`", types[0]),
+                None => format!("hole has type `{}`", types[0]),
+            };
+
+            let mut diagnostic = Diagnostic::error(format!("found hole of type `{}`", types[0]))
+                .with_code(code::E0025)
+                .with_label(hole.span, label);
+
+            if !hole.locals.is_empty() {
+                let locals = hole
+                    .locals
+                    .iter()
+                    .zip(&types[1..])
+                    .map(|((name, _), ty)| format!("\n{name}: {ty}"))
+                    .collect::<String>();
+
+                diagnostic = diagnostic.with_note(format!("locals in scope:{locals}"));
+            }
+
+            self.emitter.emit(diagnostic);
+        }
+
+        Err(LowerError)
     }
 
     fn import_root_modules(&mut self) {
@@ -387,7 +664,8 @@ impl<'a> Lowerer<'a> {
             }
 
             let Some(&submodule) = self.ir.modules[current].modules.get(segment) else {
-                let diagnostic = Diagnostic::error(format!("unresolved module: {segment}"));
+                let diagnostic = Diagnostic::error(format!("unresolved module: {segment}"))
+                    .with_code(code::E0006);
 
                 self.emitter.emit(diagnostic);
                 return Err(LowerError);
@@ -422,6 +700,12 @@ impl<'a> Lowerer<'a> {
             imported = true;
         }
 
+        if let Some(&trid) = self.ir.modules[current].traits.get(last) {
+            (self.ir.modules[module].traits).insert(last.to_string(), trid);
+
+            imported = true;
+        }
+
         if let Some(variant) = self.ir.modules[current].variants.get(last).cloned() {
             (self.ir.modules[module].variants).insert(last.to_string(), variant);
 
@@ -429,7 +713,8 @@ impl<'a> Lowerer<'a> {
         }
 
         if !imported {
-            let diagnostic = Diagnostic::error(format!("unresolved import: {last}"));
+            let diagnostic =
+                Diagnostic::error(format!("unresolved import: {last}")).with_code(code::E0007);
 
             self.emitter.emit(diagnostic);
             return Err(LowerError);
@@ -480,6 +765,7 @@ impl<'a> Lowerer<'a> {
                                 let mut locals = ir::Locals::default();
                                 let lid = locals.push(ir::Local {
                                     name: variant.name.name().to_string(),
+                                    span: variant.span,
                                     ty: ty.clone(),
                                 });
 
@@ -492,6 +778,7 @@ impl<'a> Lowerer<'a> {
 
                                 ir::Body {
                                     name: variant.name.to_string(),
+                                    span: variant.span,
                                     locals,
                                     inputs: vec![ir::Pattern {
                                         kind: ir::PatternKind::Binding(lid),
@@ -515,6 +802,7 @@ impl<'a> Lowerer<'a> {
 
                             None => ir::Body {
                                 name: variant.name.to_string(),
+                                span: variant.span,
                                 locals: ir::Locals::default(),
                                 inputs: Vec::new(),
                                 expr: Some(ir::Expr {
@@ -583,6 +871,260 @@ impl<'a> Lowerer<'a> {
         Ok(())
     }
 
+    fn lower_traits(&mut self) -> Result<(), LowerError> {
+        for (
+            trid,
+            Trait {
+                ast,
+                module,
+                bodies,
+            },
+        ) in mem::take(&mut self.traits)
+        {
+            let var = self.ir.tcx[trid].var;
+            self.ir.tcx.bounds_mut(var).traits.push(trid);
+
+            for (method, bid) in ast.methods.into_iter().zip(bodies) {
+                let mut generics = vec![(ast.generic.0.clone(), var)];
+                let mut type_lowerer = TypeLowerer {
+                    lowerer: self,
+                    module,
+                    generics: Generics::Extendable(&mut generics),
+                    allow_inferred: false,
+                };
+
+                let ty = type_lowerer.lower_type(method.ty)?;
+
+                // the impl a method refers to is found from the type of the trait
+                if !self.ir.tcx.occurs(var, &ty) {
+                    let diagnostic = Diagnostic::error(format!(
+                        "method `{}` does not mention `'{}`",
+                        method.name, ast.generic.0,
+                    ))
+                    .with_code(code::E0030)
+                    .with_label(method.name.span, "found here")
+                    .with_note("the impl a method refers to is chosen by the type of the trait");
+
+                    self.emitter.emit(diagnostic);
+                    return Err(LowerError);
+                }
+
+                self.ir[bid].ty = ty.clone();
+                self.ascribed.insert(bid);
+
+                let method = ir::Method {
+                    name: method.name.to_string(),
+                    body: bid,
+                    ty,
+                    generics: generics[1..].iter().map(|(_, var)| *var).collect(),
+                };
+
+                self.ir.tcx[trid].methods.push(method);
+            }
+
+            let name = self.ir.tcx[trid].name.clone();
+            let lang = &mut self.ir.tcx.lang;
+
+            match name.as_str() {
+                "std::eq" => lang.eq = Some(trid),
+                "std::ord" => lang.ord = Some(trid),
+                "std::hash" => lang.hash = Some(trid),
+                "std::format" => lang.format = Some(trid),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn lower_impls(&mut self) -> Result<(), LowerError> {
+        for (module, ast) in mem::take(&mut self.impls) {
+            let trid = self.resolve_trait(module, &ast.r#trait)?;
+
+            let span = ast.ty.span;
+            let mut generics = Vec::new();
+            let mut type_lowerer = TypeLowerer {
+                lowerer: self,
+                module,
+                generics: Generics::Extendable(&mut generics),
+                allow_inferred: false,
+            };
+
+            let ty = type_lowerer.lower_type(ast.ty)?;
+            let ty = self.ir.tcx.unalias(ty);
+
+            let r#trait = self.ir.tcx[trid].clone();
+
+            if let ir::Type::Var(_) = ty {
+                let diagnostic = Diagnostic::error(format!(
+                    "cannot implement `{}` for a type variable",
+                    r#trait.name,
+                ))
+                .with_code(code::E0033)
+                .with_label(span, "found here");
+
+                self.emitter.emit(diagnostic);
+                return Err(LowerError);
+            }
+
+            if !self.ir.tcx.can_implement(trid, &ty) {
+                let diagnostic = Diagnostic::error(format!(
+                    "cannot implement `{}` for `{}`",
+                    r#trait.name,
+                    self.ir.tcx.format_type(&ty),
+                ))
+                .with_code(code::E0037)
+                .with_label(span, "not a newtype declared outside of `std`")
+                .with_help("wrap the value in a newtype and implement the trait for it");
+
+                self.emitter.emit(diagnostic);
+                return Err(LowerError);
+            }
+
+            if let Some(existing) = self.ir.tcx.find_impl(trid, &ty) {
+                let diagnostic =
+                    Diagnostic::error(format!("conflicting impls of `{}`", r#trait.name))
+                        .with_code(code::E0031)
+                        .with_label(span, "conflicts with an earlier impl")
+                        .with_label(existing.span, "earlier impl here");
+
+                self.emitter.emit(diagnostic);
+                return Err(LowerError);
+            }
+
+            let [type_name] = &self.ir.tcx.format_types(std::slice::from_ref(&ty))[..] else {
+                unreachable!();
+            };
+
+            let mut methods = HashMap::new();
+
+            for function in ast.functions {
+                let name = function.name.to_string();
+
+                let Some(method) = r#trait.methods.iter().find(|method| method.name == name) else {
+                    let mut diagnostic = Diagnostic::error(format!(
+                        "`{name}` is not a method of `{}`",
+                        r#trait.name,
+                    ))
+                    .with_code(code::E0030)
+                    .with_label(function.name.span, "found here");
+
+                    let names = r#trait.methods.iter().map(|method| method.name.as_str());
+
+                    if let Some(suggestion) = suggest(&name, names) {
+                        diagnostic = diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+                    }
+
+                    self.emitter.emit(diagnostic);
+                    return Err(LowerError);
+                };
+
+                if methods.contains_key(&name) {
+                    let diagnostic =
+                        Diagnostic::error(format!("duplicate method '{name}' in impl"))
+                            .with_code(code::E0004)
+                            .with_label(function.name.span, "found here");
+
+                    self.emitter.emit(diagnostic);
+                    return Err(LowerError);
+                }
+
+                // the other generics of the method are fresh for every impl
+                let mut subst = method
+                    .generics
+                    .iter()
+                    .map(|var| (*var, ir::Type::infer(var.span())))
+                    .collect::<HashMap<_, _>>();
+
+                subst.insert(r#trait.var, ty.clone());
+
+                let body = ir::Body {
+                    name: format!("{} for {type_name}", self.ir[method.body].name),
+                    span: function.name.span,
+                    locals: ir::Locals::default(),
+                    inputs: Vec::new(),
+                    expr: None,
+                    ty: method.ty.clone().substitute(&subst),
+                };
+
+                let bid = self.ir.bodies.push(body);
+
+                let mut attrs = ast.attrs.clone();
+                attrs.attrs.extend(function.attrs.attrs.iter().cloned());
+
+                self.ir.attrs.insert(bid, attrs);
+                self.ascribed.insert(bid);
+                self.functions.insert(
+                    bid,
+                    Function {
+                        ast: function,
+                        module,
+                    },
+                );
+
+                methods.insert(name, bid);
+            }
+
+            let missing = r#trait
+                .methods
+                .iter()
+                .filter(|method| !methods.contains_key(&method.name))
+                .map(|method| format!("`{}`", method.name))
+                .collect::<Vec<_>>();
+
+            if !missing.is_empty() {
+                let diagnostic = Diagnostic::error(format!(
+                    "missing methods in impl of `{}`: {}",
+                    r#trait.name,
+                    missing.join(", "),
+                ))
+                .with_code(code::E0030)
+                .with_label(ast.span, "impl found here");
+
+                self.emitter.emit(diagnostic);
+                return Err(LowerError);
+            }
+
+            let r#impl = ir::Impl {
+                r#trait: trid,
+                ty,
+                methods,
+                span: ast.span,
+            };
+
+            self.ir.tcx.push_impl(r#impl);
+        }
+
+        Ok(())
+    }
+
+    fn resolve_trait(&mut self, module: ir::Mid, path: &ast::Path) -> Result<ir::Trid, LowerError> {
+        self.use_path(module, path);
+
+        let found = self.ir.get_module(module, path.modules());
+
+        if let Some(found) = found
+            && let Some(&trid) = self.ir[found].traits.get(path.name())
+        {
+            return Ok(trid);
+        }
+
+        let mut diagnostic = Diagnostic::error(format!("unresolved trait: {path}"))
+            .with_code(code::E0032)
+            .with_label(path.span, "found here");
+
+        if let Some(found) = found {
+            let names = self.ir[found].traits.keys().map(String::as_str);
+
+            if let Some(suggestion) = suggest(path.name(), names) {
+                diagnostic = diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+            }
+        }
+
+        self.emitter.emit(diagnostic);
+        Err(LowerError)
+    }
+
     fn lower_externs(&mut self) -> Result<(), LowerError> {
         for (bid, Extern { ast, module }) in mem::take(&mut self.externs) {
             let mut generics = Vec::new();
@@ -607,6 +1149,25 @@ impl<'a> Lowerer<'a> {
             return Ok(());
         };
 
+        self.components.enter(bid);
+        let result = self.lower_function_body(bid, function);
+        let members = self.components.leave(bid);
+
+        // the component is inferred, so the bounds of its types are known
+        let (closed, open) = mem::take(&mut self.instances)
+            .into_iter()
+            .partition::<Vec<_>, _>(|instance| members.contains(&instance.bid));
+
+        self.instances = open;
+
+        for instance in closed {
+            self.ir.tcx.bound_instance(&instance.vars, instance.span);
+        }
+
+        result
+    }
+
+    fn lower_function_body(&mut self, bid: ir::Bid, function: Function) -> Result<(), LowerError> {
         let mut lowerer = ExprLowerer {
             lowerer: self,
 
@@ -623,6 +1184,8 @@ impl<'a> Lowerer<'a> {
             params.push(ty.clone());
 
             let pattern = lowerer.lower_pattern(pattern, ty)?;
+            lowerer.irrefutable(&pattern, exhaust::MatchSource::Parameter);
+
             lowerer.body_mut().inputs.push(pattern);
         }
 
@@ -673,8 +1236,11 @@ impl TypeLowerer<'_, '_> {
             ast::TypeKind::Unit => ir::Type::unit(ast.span),
 
             ast::TypeKind::Path(path, generics) => {
+                self.lowerer.use_path(self.module, &path);
+
                 let Some(module) = self.ir.get_module(self.module, path.modules()) else {
                     let diagnostic = Diagnostic::error(format!("unresolved module: {path}"))
+                        .with_code(code::E0006)
                         .with_label(path.span, "found here");
 
                     self.lowerer.emitter.emit(diagnostic);
@@ -682,9 +1248,16 @@ impl TypeLowerer<'_, '_> {
                 };
 
                 let Some(&tid) = self.ir[module].newtypes.get(path.name()) else {
-                    let diagnostic = Diagnostic::error(format!("unresolved type: {path}"))
+                    let mut diagnostic = Diagnostic::error(format!("unresolved type: {path}"))
+                        .with_code(code::E0008)
                         .with_label(path.span, "found here");
 
+                    let names = self.ir[module].newtypes.keys().map(String::as_str);
+
+                    if let Some(suggestion) = suggest(path.name(), names) {
+                        diagnostic = diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+                    }
+
                     self.lowerer.emitter.emit(diagnostic);
                     return Err(LowerError);
                 };
@@ -696,6 +1269,7 @@ impl TypeLowerer<'_, '_> {
                         self.ir.tcx[tid].generics.len(),
                         generics.len()
                     ))
+                    .with_code(code::E0009)
                     .with_label(path.span, "found here");
 
                     self.lowerer.emitter.emit(diagnostic);
@@ -743,6 +1317,7 @@ impl TypeLowerer<'_, '_> {
                 }
 
                 let diagnostic = Diagnostic::error(format!("unresolved generic type: {name}"))
+                    .with_code(code::E0010)
                     .with_label(ast.span, "found here");
 
                 self.lowerer.emitter.emit(diagnostic);
@@ -752,6 +1327,7 @@ impl TypeLowerer<'_, '_> {
             ast::TypeKind::Inferred => {
                 if !self.allow_inferred {
                     let diagnostic = Diagnostic::error("inferred type not allowed")
+                        .with_code(code::E0011)
                         .with_label(ast.span, "found here");
 
                     self.lowerer.emitter.emit(diagnostic);
@@ -850,18 +1426,111 @@ impl ExprLowerer<'_, '_> {
         )
     }
 
+    /// The names of every local in scope, including those of parent bodies
+    /// that could be captured.
+    fn local_names(&self) -> Vec<String> {
+        let parents = self.parents.iter().flat_map(|(bid, scope)| {
+            let locals = &self.ir[*bid].locals;
+            scope.iter().map(move |(lid, _)| locals[*lid].name.clone())
+        });
+
+        let locals = &self.body().locals;
+        let scope = self.scope.iter().map(|lid| locals[*lid].name.clone());
+
+        parents.chain(scope).collect()
+    }
+
+    /// The locals in scope and their types, without those that are shadowed.
+    fn locals_in_scope(&self) -> Vec<(String, ir::Type)> {
+        let parents = self.parents.iter().flat_map(|(bid, scope)| {
+            let locals = &self.ir[*bid].locals;
+            scope.iter().map(move |(lid, _)| &locals[*lid])
+        });
+
+        let locals = &self.body().locals;
+        let scope = self.scope.iter().map(|lid| &locals[*lid]);
+
+        let mut in_scope: Vec<(String, ir::Type)> = Vec::new();
+
+        for local in parents.chain(scope) {
+            in_scope.retain(|(name, _)| *name != local.name);
+            in_scope.push((local.name.clone(), local.ty.clone()));
+        }
+
+        in_scope
+    }
+
+    /// Whether `expr` is a literal zero, possibly negated or ascribed.
+    fn is_zero(expr: &ast::Expr) -> bool {
+        match expr.kind {
+            ast::ExprKind::Int(value) => value == 0,
+            ast::ExprKind::Unary(ast::UnOp::Neg, _, ref expr) => Self::is_zero(expr),
+            ast::ExprKind::Ascription(ref expr, _) => Self::is_zero(expr),
+            _ => false,
+        }
+    }
+
     fn number(&mut self, ty: ir::Type, span: Span) {
         self.lowerer.ir.tcx.number(ty, span);
     }
 
-    fn unify(&mut self, lhs: ir::Type, rhs: ir::Type, span: Span) {
-        self.lowerer.ir.tcx.unify(lhs, rhs, span);
+    fn equatable(&mut self, ty: ir::Type, span: Span) {
+        self.lowerer.ir.tcx.equatable(ty, span);
+    }
+
+    fn orderable(&mut self, ty: ir::Type, span: Span) {
+        self.lowerer.ir.tcx.orderable(ty, span);
+    }
+
+    fn unify(&mut self, found: ir::Type, expected: ir::Type, span: Span) {
+        self.lowerer.ir.tcx.unify(found, expected, span);
     }
 
     fn field(&mut self, target: ir::Type, name: &str, ty: ir::Type, span: Span) {
         self.lowerer.ir.tcx.field(target, name, ty, span);
     }
 
+    /// Resolve `path` to the record it names.
+    fn resolve_record(&mut self, path: &ast::Path) -> Result<(ir::Tid, ir::Record), LowerError> {
+        self.lowerer.use_path(self.module, path);
+
+        let Some(module) = self.ir.get_module(self.module, path.modules()) else {
+            let diagnostic = Diagnostic::error(format!("unresolved module: {path}"))
+                .with_code(code::E0006)
+                .with_label(path.span, "found here");
+
+            self.lowerer.emitter.emit(diagnostic);
+            return Err(LowerError);
+        };
+
+        let Some(&tid) = self.ir[module].newtypes.get(path.name()) else {
+            let diagnostic = Diagnostic::error(format!("unresolved type: {path}"))
+                .with_code(code::E0008)
+                .with_label(path.span, "found here");
+
+            self.lowerer.emitter.emit(diagnostic);
+            return Err(LowerError);
+        };
+
+        let newtype = &self.ir.tcx[tid];
+        let ir::NewtypeKind::Record(ref record) = newtype.kind else {
+            let found = match newtype.kind {
+                ir::NewtypeKind::Record(_) => "record",
+                ir::NewtypeKind::Union(_) => "union",
+                ir::NewtypeKind::Alias(_) => "alias",
+            };
+
+            let diagnostic = Diagnostic::error(format!("expected record, found `{found}`"))
+                .with_code(code::E0017)
+                .with_label(path.span, "found here");
+
+            self.lowerer.emitter.emit(diagnostic);
+            return Err(LowerError);
+        };
+
+        Ok((tid, record.clone()))
+    }
+
     fn lower_expr(&mut self, ast: ast::Expr) -> Result<ir::Expr, LowerError> {
         Ok(match ast.kind {
             ast::ExprKind::Int(value) => ir::Expr {
@@ -906,6 +1575,9 @@ impl ExprLowerer<'_, '_> {
                     }
                 }
 
+                self.lowerer.use_path(self.module, &path);
+
+                let is_single = path.segments().len() == 1;
                 let name = path.segments().last().unwrap();
                 let path = path.modules();
 
@@ -915,19 +1587,41 @@ impl ExprLowerer<'_, '_> {
                     if let Some(&bid) = self.ir[module].bodies.get(name) {
                         let this_bid = self.body;
 
-                        let called_from = self.call_graph.entry(bid).or_default();
-                        called_from.insert(this_bid);
+                        // calls made from within lambdas are attributed to the enclosing function
+                        if !self.ir[module].variants.contains_key(name) {
+                            let caller = self.parents.first().map_or(this_bid, |(bid, _)| *bid);
 
-                        self.lower_function(bid)?;
+                            self.ir.calls.push(ir::Call {
+                                caller,
+                                callee: bid,
+                                span: ast.span,
+                            });
+                        }
 
-                        let callers = self.call_graph.get(&this_bid).cloned().unwrap_or_default();
-                        let called_from = self.call_graph.entry(bid).or_default();
-                        called_from.extend(callers);
+                        // functions with an ascription are generic over their declared type,
+                        // every other function is only generic once its component is inferred,
+                        // either way the body is lowered first to learn the bounds of its type
+                        let function = self.parents.first().map_or(this_bid, |(bid, _)| *bid);
+
+                        self.lower_function(bid)?;
+                        let recursive = self.components.refer(function, bid);
+                        let ascribed = self.ascribed.contains(&bid);
 
                         let mut ty = self.ir[bid].ty.clone();
                         ty = self.ir.tcx.substitute(ty);
 
-                        if (self.call_graph.get(&this_bid)).is_none_or(|c| !c.contains(&bid)) {
+                        // within its component the body of an ascribed function may not
+                        // have been lowered yet, so its bounds are given once it has
+                        if ascribed && recursive {
+                            let (instance, vars) = self.ir.tcx.instantiate_with_vars(ty);
+                            ty = instance;
+
+                            self.lowerer.instances.push(Instance {
+                                bid,
+                                vars,
+                                span: ast.span,
+                            });
+                        } else if !recursive {
                             ty = self.ir.tcx.instantiate(ty);
                         }
 
@@ -941,9 +1635,24 @@ impl ExprLowerer<'_, '_> {
                     }
                 }
 
-                let diagnostic = Diagnostic::error(format!("unresolved path: {name}"))
+                let mut diagnostic = Diagnostic::error(format!("unresolved path: {name}"))
+                    .with_code(code::E0012)
                     .with_label(ast.span, "found here");
 
+                let mut candidates = Vec::new();
+
+                if is_single {
+                    candidates.extend(self.local_names());
+                }
+
+                if let Some(module) = module {
+                    candidates.extend(self.ir[module].bodies.keys().cloned());
+                }
+
+                if let Some(suggestion) = suggest(name, candidates.iter().map(String::as_str)) {
+                    diagnostic = diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+                }
+
                 self.lowerer.emitter.emit(diagnostic);
 
                 return Err(LowerError);
@@ -953,15 +1662,7 @@ impl ExprLowerer<'_, '_> {
                 let span = expr.span;
                 let value = self.lower_expr(*expr)?;
                 let pattern = self.lower_pattern(pattern, value.ty.clone())?;
-
-                if pattern.kind.is_refutable() {
-                    let diagnostic = Diagnostic::error("refutable pattern in let binding")
-                        .with_label(pattern.span, "pattern is refutable")
-                        .with_label(value.span, "value is here");
-
-                    self.lowerer.emitter.emit(diagnostic);
-                    return Err(LowerError);
-                }
+                self.irrefutable(&pattern, exhaust::MatchSource::Let(value.span));
 
                 ir::Expr {
                     kind: ir::ExprKind::Let(pattern, Box::new(value)),
@@ -971,36 +1672,8 @@ impl ExprLowerer<'_, '_> {
             }
 
             ast::ExprKind::Record(path, fields) => {
-                let Some(module) = self.ir.get_module(self.module, path.modules()) else {
-                    let diagnostic = Diagnostic::error(format!("unresolved module: {path}"))
-                        .with_label(path.span, "found here");
-
-                    self.lowerer.emitter.emit(diagnostic);
-                    return Err(LowerError);
-                };
-
-                let Some(&tid) = self.ir[module].newtypes.get(path.name()) else {
-                    let diagnostic = Diagnostic::error(format!("unresolved type: {path}"))
-                        .with_label(path.span, "found here");
-
-                    self.lowerer.emitter.emit(diagnostic);
-                    return Err(LowerError);
-                };
-
-                let newtype = self.ir.tcx[tid].clone();
-                let ir::NewtypeKind::Record(record) = newtype.kind else {
-                    let found = match newtype.kind {
-                        ir::NewtypeKind::Record(_) => "record",
-                        ir::NewtypeKind::Union(_) => "union",
-                        ir::NewtypeKind::Alias(_) => "alias",
-                    };
-
-                    let diagnostic = Diagnostic::error(format!("expected record, found `{found}`"))
-                        .with_label(path.span, "found here");
-
-                    self.lowerer.emitter.emit(diagnostic);
-                    return Err(LowerError);
-                };
+                let (tid, record) = self.resolve_record(&path)?;
+                let newtype = &self.ir.tcx[tid];
 
                 let mut generics = Vec::new();
 
@@ -1021,11 +1694,19 @@ impl ExprLowerer<'_, '_> {
                     let value = self.lower_expr(value)?;
 
                     let Some(field) = record.field(&name) else {
-                        let diagnostic = Diagnostic::error(format!(
+                        let mut diagnostic = Diagnostic::error(format!(
                             "type '{path}' does not have field '{name}'"
                         ))
+                        .with_code(code::E0018)
                         .with_label(value.span, "found here");
 
+                        let names = record.fields.iter().map(|field| field.name.as_str());
+
+                        if let Some(suggestion) = suggest(&name, names) {
+                            diagnostic =
+                                diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+                        }
+
                         self.lowerer.emitter.emit(diagnostic);
                         return Err(LowerError);
                     };
@@ -1036,6 +1717,7 @@ impl ExprLowerer<'_, '_> {
                     if ir_fields.iter().any(|(n, _)| n == &name) {
                         let diagnostic =
                             Diagnostic::error(format!("duplicate field '{name}' in record"))
+                                .with_code(code::E0020)
                                 .with_label(value.span, "found here");
 
                         self.lowerer.emitter.emit(diagnostic);
@@ -1051,6 +1733,7 @@ impl ExprLowerer<'_, '_> {
                             "missing field '{}' in record '{}'",
                             field.name, path
                         ))
+                        .with_code(code::E0021)
                         .with_label(ast.span, "found here");
 
                         self.lowerer.emitter.emit(diagnostic);
@@ -1074,6 +1757,7 @@ impl ExprLowerer<'_, '_> {
                         let diagnostic = Diagnostic::error(format!(
                             "duplicate field '{name}' in with expression"
                         ))
+                        .with_code(code::E0020)
                         .with_label(span, "found here");
 
                         self.lowerer.emitter.emit(diagnostic);
@@ -1150,7 +1834,7 @@ impl ExprLowerer<'_, '_> {
                 let expr = self.lower_expr(*value)?;
 
                 let result_ty = ir::Type::newtype(result, args, ast.span);
-                self.unify(result_ty, expr.ty.clone(), ast.span);
+                self.unify(expr.ty.clone(), result_ty, ast.span);
 
                 let return_ty = ir::Type::newtype(
                     result, //
@@ -1183,7 +1867,7 @@ impl ExprLowerer<'_, '_> {
                     output.clone(),
                     input.span, //
                 );
-                self.unify(callee.ty.clone(), function.clone(), ast.span);
+                self.unify(function.clone(), callee.ty.clone(), ast.span);
 
                 ir::Expr {
                     kind: ir::ExprKind::Call(Box::new(callee), Box::new(input)),
@@ -1195,6 +1879,7 @@ impl ExprLowerer<'_, '_> {
             ast::ExprKind::Lambda(params, expr) => {
                 let body = ir::Body {
                     name: format!("{}::{{lambda}}", self.body().name),
+                    span: ast.span,
                     locals: ir::Locals::default(),
                     inputs: Vec::new(),
                     expr: None,
@@ -1203,6 +1888,11 @@ impl ExprLowerer<'_, '_> {
 
                 let bid = self.ir.bodies.push(body);
 
+                // lambdas share the lint levels of the function they are defined in
+                if let Some(attrs) = self.ir.attrs.get(&self.body).cloned() {
+                    self.ir.attrs.insert(bid, attrs);
+                }
+
                 let mut lowerer = ExprLowerer {
                     lowerer: self.lowerer,
                     body: bid,
@@ -1219,14 +1909,7 @@ impl ExprLowerer<'_, '_> {
                 for param in params {
                     let ty = ir::Type::infer(param.span);
                     let pattern = lowerer.lower_pattern(param, ty.clone())?;
-
-                    if pattern.kind.is_refutable() {
-                        let diagnostic = Diagnostic::error("refutable pattern in lambda")
-                            .with_label(pattern.span, "pattern is refutable");
-
-                        lowerer.lowerer.emitter.emit(diagnostic);
-                        return Err(LowerError);
-                    }
+                    lowerer.irrefutable(&pattern, exhaust::MatchSource::Lambda);
 
                     patterns.push((pattern, ty));
                 }
@@ -1300,6 +1983,31 @@ impl ExprLowerer<'_, '_> {
                 expr
             }
 
+            ast::ExprKind::Unary(op, op_span, value) => {
+                let value = self.lower_expr(*value)?;
+
+                let (op, ty) = match op {
+                    ast::UnOp::Neg => {
+                        self.number(value.ty.clone(), op_span);
+                        (ir::UnOp::Neg, value.ty.clone())
+                    }
+
+                    ast::UnOp::Not => {
+                        self.unify(value.ty.clone(), ir::Type::bool(op_span), value.span);
+                        (ir::UnOp::Not, ir::Type::bool(ast.span))
+                    }
+
+                    ast::UnOp::BitNot => {
+                        self.unify(value.ty.clone(), ir::Type::int(op_span), value.span);
+                        (ir::UnOp::BitNot, ir::Type::int(ast.span))
+                    }
+                };
+
+                let span = ast.span;
+                let kind = ir::ExprKind::Unary(op, Box::new(value));
+                ir::Expr { kind, span, ty }
+            }
+
             ast::ExprKind::Binary(op, op_span, lhs, rhs) => {
                 let op = match op {
                     ast::BinOp::Add => ir::BinOp::Add,
@@ -1310,6 +2018,9 @@ impl ExprLowerer<'_, '_> {
                     ast::BinOp::And => ir::BinOp::And,
                     ast::BinOp::Shl => ir::BinOp::Shl,
                     ast::BinOp::Shr => ir::BinOp::Shr,
+                    ast::BinOp::BitAnd => ir::BinOp::BitAnd,
+                    ast::BinOp::BitOr => ir::BinOp::BitOr,
+                    ast::BinOp::BitXor => ir::BinOp::BitXor,
                     ast::BinOp::Or => ir::BinOp::Or,
                     ast::BinOp::Gt => ir::BinOp::Gt,
                     ast::BinOp::Lt => ir::BinOp::Lt,
@@ -1319,6 +2030,41 @@ impl ExprLowerer<'_, '_> {
                     ast::BinOp::Ne => ir::BinOp::Ne,
                 };
 
+                if matches!(op, ir::BinOp::Div | ir::BinOp::Mod) && Self::is_zero(&rhs) {
+                    let diagnostic = Diagnostic::error("division by zero")
+                        .with_code(code::E0036)
+                        .with_label(op_span, "this operation will panic at runtime")
+                        .with_secondary_label(rhs.span, "the divisor is zero");
+
+                    self.lowerer.emitter.emit(diagnostic);
+                    return Err(LowerError);
+                }
+
+                // `f | x | x` is bitwise or, see `parse_lambda_expr`, when `x`
+                // does not resolve it was most likely meant to be a lambda
+                if let ir::BinOp::BitOr = op
+                    && let ast::ExprKind::Binary(ast::BinOp::BitOr, pipe, _, ref param) = lhs.kind
+                    && let ast::ExprKind::Path(ref path) = param.kind
+                    && path.segments().len() == 1
+                    && self.find_local(path.name()).is_none()
+                    && !self
+                        .ir
+                        .get_module(self.module, path.modules())
+                        .is_some_and(|module| self.ir[module].bodies.contains_key(path.name()))
+                {
+                    let name = path.name();
+                    let diagnostic = Diagnostic::error(format!("unresolved path: {name}"))
+                        .with_code(code::E0012)
+                        .with_label(param.span, "found here")
+                        .with_secondary_label(pipe, "`|` after an argument is bitwise or")
+                        .with_help(format!(
+                            "to pass a lambda, put parentheses around it: `(|{name}| ..)`"
+                        ));
+
+                    self.lowerer.emitter.emit(diagnostic);
+                    return Err(LowerError);
+                }
+
                 let lhs = self.lower_expr(*lhs)?;
                 let rhs = self.lower_expr(*rhs)?;
 
@@ -1330,21 +2076,29 @@ impl ExprLowerer<'_, '_> {
                     | ir::BinOp::Mod
                     | ir::BinOp::Shl
                     | ir::BinOp::Shr => {
-                        self.unify(lhs.ty.clone(), rhs.ty.clone(), ast.span);
+                        self.unify(rhs.ty.clone(), lhs.ty.clone(), ast.span);
                         self.number(lhs.ty.clone(), op_span);
 
                         lhs.ty.clone()
                     }
 
+                    ir::BinOp::BitAnd | ir::BinOp::BitOr | ir::BinOp::BitXor => {
+                        self.unify(lhs.ty.clone(), ir::Type::int(op_span), lhs.span);
+                        self.unify(rhs.ty.clone(), ir::Type::int(op_span), rhs.span);
+
+                        ir::Type::int(ast.span)
+                    }
+
                     ir::BinOp::Gt | ir::BinOp::Lt | ir::BinOp::Ge | ir::BinOp::Le => {
-                        self.unify(lhs.ty.clone(), rhs.ty.clone(), ast.span);
-                        self.number(lhs.ty.clone(), op_span);
+                        self.unify(rhs.ty.clone(), lhs.ty.clone(), ast.span);
+                        self.orderable(lhs.ty.clone(), op_span);
 
                         ir::Type::bool(ast.span)
                     }
 
                     ir::BinOp::Eq | ir::BinOp::Ne => {
-                        self.unify(lhs.ty.clone(), rhs.ty.clone(), ast.span);
+                        self.unify(rhs.ty.clone(), lhs.ty.clone(), ast.span);
+                        self.equatable(lhs.ty.clone(), op_span);
 
                         ir::Type::bool(ast.span)
                     }
@@ -1385,20 +2139,90 @@ impl ExprLowerer<'_, '_> {
                     let old_scope_len = self.scope.len();
 
                     let pattern = self.lower_pattern(arm.pattern, target.ty.clone())?;
+
+                    let guard = match arm.guard {
+                        Some(guard) => {
+                            let guard = self.lower_expr(guard)?;
+                            let bool = ir::Type::bool(guard.span);
+                            self.unify(guard.ty.clone(), bool, guard.span);
+
+                            Some(guard)
+                        }
+
+                        None => None,
+                    };
+
                     let expr = self.lower_expr(arm.expr)?;
                     self.unify(expr.ty.clone(), ty.clone(), expr.span);
-                    ir_arms.push(ir::Arm { pattern, expr });
+                    ir_arms.push(ir::Arm {
+                        pattern,
+                        guard,
+                        expr,
+                    });
 
                     self.scope.truncate(old_scope_len);
                 }
 
-                self.exhaust(&ir_arms, ast.span)?;
+                let arms = ir_arms
+                    .iter()
+                    .map(|arm| (arm.pattern.clone(), arm.guard.is_some()));
+
+                self.lowerer.matches.push(exhaust::Match {
+                    body: self.body,
+                    span: ast.span,
+                    source: exhaust::MatchSource::Match,
+                    arms: arms.collect(),
+                });
 
                 let span = ast.span;
                 let kind = ir::ExprKind::Match(Box::new(target), ir_arms);
                 ir::Expr { kind, span, ty }
             }
 
+            // `if` is a `match` on `true` and `false`
+            ast::ExprKind::If(condition, then, r#else) => {
+                let condition = self.lower_expr(*condition)?;
+                let bool = ir::Type::bool(condition.span);
+                self.unify(condition.ty.clone(), bool, condition.span);
+
+                let then = self.lower_expr(*then)?;
+
+                let r#else = match r#else {
+                    Some(r#else) => {
+                        let r#else = self.lower_expr(*r#else)?;
+                        self.unify(r#else.ty.clone(), then.ty.clone(), r#else.span);
+                        r#else
+                    }
+
+                    // without an `else`, the `if` is only run for its effects
+                    None => {
+                        self.unify(then.ty.clone(), ir::Type::unit(then.span), then.span);
+
+                        ir::Expr {
+                            kind: ir::ExprKind::Block(Vec::new()),
+                            span: ast.span,
+                            ty: ir::Type::unit(ast.span),
+                        }
+                    }
+                };
+
+                let arm = |value, expr: ir::Expr| ir::Arm {
+                    pattern: ir::Pattern {
+                        kind: ir::PatternKind::Bool(value),
+                        span: expr.span,
+                    },
+                    guard: None,
+                    expr,
+                };
+
+                let ty = then.ty.clone();
+                let arms = vec![arm(true, then), arm(false, r#else)];
+
+                let span = ast.span;
+                let kind = ir::ExprKind::Match(Box::new(condition), arms);
+                ir::Expr { kind, span, ty }
+            }
+
             ast::ExprKind::Block(ast_exprs) => {
                 let mut ir_exprs = Vec::new();
                 let mut ty = ir::Type::unit(ast.span);
@@ -1421,216 +2245,49 @@ impl ExprLowerer<'_, '_> {
                     ty,
                 }
             }
-        })
-    }
-
-    fn exhaust(&mut self, arms: &[ir::Arm], span: Span) -> Result<(), LowerError> {
-        let matrix = Matrix::new(arms);
-        let exhaustive = self.exhaust_impl(matrix);
 
-        if !exhaustive {
-            let diagnostic = Diagnostic::error("non-exhaustive patterns in match expression")
-                .with_label(span, "found here");
-
-            self.lowerer.emitter.emit(diagnostic);
-            return Err(LowerError);
-        }
-
-        Ok(())
-    }
-
-    fn exhaust_impl(&mut self, matrix: Matrix) -> bool {
-        let cons = match matrix.pattern() {
-            Some(pattern) => match pattern.kind {
-                ir::PatternKind::Bool(_) => vec![Cons::Bool(true), Cons::Bool(false)],
-                ir::PatternKind::Int(_) => vec![Cons::Int],
-                ir::PatternKind::String(_) => vec![Cons::String],
-                ir::PatternKind::ListEmpty => vec![Cons::List(false), Cons::List(true)],
-                ir::PatternKind::ListCons(_, _) => vec![Cons::List(false), Cons::List(true)],
-
-                ir::PatternKind::Variant(ref ty, _, _) => {
-                    let ir::Type::App(ir::App {
-                        kind: ir::AppKind::Newtype(tid, _),
-                        ..
-                    }) = ty
-                    else {
-                        unreachable!();
-                    };
-
-                    let newtype = &self.ir.tcx[*tid];
-                    let ir::NewtypeKind::Union(ref union) = newtype.kind else {
-                        unreachable!();
-                    };
-
-                    let mut cons = Vec::new();
-
-                    for variant in &union.variants {
-                        cons.push(Cons::Variant(variant.name.clone(), variant.ty.is_some()));
-                    }
+            ast::ExprKind::Hole(name) => {
+                let ty = ir::Type::infer(ast.span);
 
-                    cons
-                }
+                let hole = Hole {
+                    name,
+                    span: ast.span,
+                    ty: ty.clone(),
+                    locals: self.locals_in_scope(),
+                };
 
-                ir::PatternKind::Tuple(ref items) => vec![Cons::Tuple(items.len())],
+                self.holes.push(hole);
 
-                ir::PatternKind::Wildcard | ir::PatternKind::Binding(_) => {
-                    unreachable!();
+                ir::Expr {
+                    kind: ir::ExprKind::Hole,
+                    span: ast.span,
+                    ty,
                 }
-            },
-            None => vec![Cons::Wildcard],
-        };
-
-        for cons in cons {
-            let matrix = match matrix.specialize(&cons) {
-                Some(matrix) => matrix,
-                None => return false,
-            };
-
-            if matrix.len() == 0 {
-                continue;
-            }
-
-            if !self.exhaust_impl(matrix) {
-                return false;
             }
-        }
-
-        true
-    }
-}
-
-#[derive(Debug)]
-struct MatrixRow {
-    patterns: Vec<ir::Pattern>,
-}
-
-impl MatrixRow {
-    fn new(arm: &ir::Arm) -> Self {
-        Self {
-            patterns: vec![arm.pattern.clone()],
-        }
-    }
-
-    fn specialize(&self, cons: &Cons) -> Option<Self> {
-        let pattern = &self.patterns[0];
-        let mut patterns = cons.specialize(pattern)?;
-        patterns.extend(self.patterns.iter().skip(1).cloned());
-        Some(Self { patterns })
-    }
-}
-
-#[derive(Debug)]
-struct Matrix {
-    rows: Vec<MatrixRow>,
-}
 
-impl Matrix {
-    fn new(args: &[ir::Arm]) -> Self {
-        let mut rows = Vec::new();
+            ast::ExprKind::Ascription(expr, ascribed) => {
+                let expr = self.lower_expr(*expr)?;
 
-        for arm in args {
-            rows.push(MatrixRow::new(arm));
-        }
+                let ascribed = self.lower_ascribed_type(ascribed)?;
+                self.unify(expr.ty.clone(), ascribed, ast.span);
 
-        Self { rows }
-    }
-
-    fn len(&self) -> usize {
-        self.rows[0].patterns.len()
-    }
-
-    fn pattern(&self) -> Option<&ir::Pattern> {
-        for row in self.rows.iter() {
-            let pattern = &row.patterns[0];
-
-            if !matches!(
-                pattern.kind,
-                ir::PatternKind::Wildcard | ir::PatternKind::Binding(_)
-            ) {
-                return Some(pattern);
-            }
-        }
-
-        None
-    }
-
-    fn specialize(&self, cons: &Cons) -> Option<Self> {
-        let mut rows = Vec::new();
-
-        for row in &self.rows {
-            if let Some(specialized_row) = row.specialize(cons) {
-                rows.push(specialized_row);
+                expr
             }
-        }
-
-        match rows.is_empty() {
-            false => Some(Self { rows }),
-            true => None,
-        }
-    }
-}
-
-#[derive(Debug)]
-enum Cons {
-    String,
-    Int,
-    Bool(bool),
-    List(bool),
-    Tuple(usize),
-    Variant(String, bool),
-    Wildcard,
-}
-
-impl Cons {
-    fn arity(&self) -> usize {
-        match self {
-            Cons::String
-            | Cons::Int
-            | Cons::Bool(_)
-            | Cons::List(false)
-            | Cons::Variant(_, false)
-            | Cons::Wildcard => 0,
-
-            Cons::List(true) => 2,
-            Cons::Tuple(len) => *len,
-            Cons::Variant(_, true) => 1,
-        }
+        })
     }
 
-    fn specialize(&self, pattern: &ir::Pattern) -> Option<Vec<ir::Pattern>> {
-        match (self, &pattern.kind) {
-            (Cons::String, ir::PatternKind::String(_)) => None,
-            (Cons::Int, ir::PatternKind::Int(_)) => None,
-
-            (Cons::Bool(true), ir::PatternKind::Bool(true)) => Some(Vec::new()),
-            (Cons::Bool(false), ir::PatternKind::Bool(false)) => Some(Vec::new()),
-
-            (Cons::Tuple(_), ir::PatternKind::Tuple(items)) => Some(items.clone()),
-
-            (Cons::List(false), ir::PatternKind::ListEmpty) => Some(Vec::new()),
-
-            (Cons::List(true), ir::PatternKind::ListCons(head, tail)) => {
-                Some(vec![head.as_ref().clone(), tail.as_ref().clone()])
-            }
-
-            (Cons::Variant(c, _), ir::PatternKind::Variant(_, v, p)) if c == v => match p {
-                Some(inner) => Some(vec![inner.as_ref().clone()]),
-                None => Some(Vec::new()),
-            },
-
-            (Cons::Wildcard, _) => Some(Vec::new()),
-
-            (cons, ir::PatternKind::Wildcard | ir::PatternKind::Binding(_)) => {
-                let wildcard = ir::Pattern {
-                    kind: ir::PatternKind::Wildcard,
-                    span: pattern.span,
-                };
-
-                Some(vec![wildcard; cons.arity()])
-            }
+    /// Lower a type written in an expression or pattern, where `_` is allowed
+    /// and generics are local to the type.
+    fn lower_ascribed_type(&mut self, ast: ast::Type) -> Result<ir::Type, LowerError> {
+        let mut generics = Vec::new();
+        let mut type_lowerer = TypeLowerer {
+            lowerer: self.lowerer,
+            module: self.module,
+            generics: Generics::Extendable(&mut generics),
+            allow_inferred: true,
+        };
 
-            (_, _) => None,
-        }
+        type_lowerer.lower_type(ast)
     }
 }
 
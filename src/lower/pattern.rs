@@ -1,6 +1,10 @@
 use std::collections::HashMap;
 
-use crate::{ast, diagnostic::Diagnostic, ir::untyped as ir};
+use crate::{
+    ast,
+    diagnostic::{Diagnostic, Span, code, suggest},
+    ir::untyped as ir,
+};
 
 use super::{ExprLowerer, LowerError};
 
@@ -17,6 +21,8 @@ impl ExprLowerer<'_, '_> {
             },
 
             ast::PatternKind::Path(path) => {
+                self.lowerer.use_path(self.module, &path);
+
                 if let Some(module) = self.ir.get_module(self.module, path.modules()) {
                     if let Some((tid, variant)) = self.ir[module].variants.get(path.name()).cloned()
                     {
@@ -41,6 +47,7 @@ impl ExprLowerer<'_, '_> {
                     let diagnostic = Diagnostic::error(format!(
                         "invalid pattern path: '{path}', expected a single segment"
                     ))
+                    .with_code(code::E0014)
                     .with_label(path.span, "found here");
 
                     self.lowerer.emitter.emit(diagnostic);
@@ -49,6 +56,7 @@ impl ExprLowerer<'_, '_> {
 
                 let lid = self.body_mut().locals.push(ir::Local {
                     name: path.name().to_string(),
+                    span: ast.span,
                     ty: ty.clone(),
                 });
 
@@ -61,9 +69,12 @@ impl ExprLowerer<'_, '_> {
             }
 
             ast::PatternKind::Variant(path, pattern) => {
+                self.lowerer.use_path(self.module, &path);
+
                 // get the module of the path
                 let Some(module) = self.ir.get_module(self.module, path.modules()) else {
                     let diagnostic = Diagnostic::error(format!("unresolved module: {path}"))
+                        .with_code(code::E0006)
                         .with_label(path.span, "found here");
 
                     self.lowerer.emitter.emit(diagnostic);
@@ -73,9 +84,16 @@ impl ExprLowerer<'_, '_> {
                 // get the variant in the module
                 let Some((tid, variant)) = self.ir[module].variants.get(path.name()).cloned()
                 else {
-                    let diagnostic = Diagnostic::error(format!("unresolved variant: {path}"))
+                    let mut diagnostic = Diagnostic::error(format!("unresolved variant: {path}"))
+                        .with_code(code::E0013)
                         .with_label(path.span, "found here");
 
+                    let names = self.ir[module].variants.keys().map(String::as_str);
+
+                    if let Some(suggestion) = suggest(path.name(), names) {
+                        diagnostic = diagnostic.with_help(format!("did you mean `{suggestion}`?"));
+                    }
+
                     self.lowerer.emitter.emit(diagnostic);
                     return Err(LowerError);
                 };
@@ -119,7 +137,8 @@ impl ExprLowerer<'_, '_> {
                             let diagnostic = Diagnostic::error(format!(
                                 "variant '{}' in union '{}' does not have a type",
                                 variant.name, newtype.name
-                            ));
+                            ))
+                            .with_code(code::E0015);
 
                             self.lowerer.emitter.emit(diagnostic);
                             return Err(LowerError);
@@ -154,7 +173,7 @@ impl ExprLowerer<'_, '_> {
                 }
 
                 let tuple_ty = ir::Type::tuple(types, ast.span);
-                self.unify(ty, tuple_ty, ast.span);
+                self.unify(tuple_ty, ty, ast.span);
 
                 ir::Pattern {
                     kind: ir::PatternKind::Tuple(patterns),
@@ -163,7 +182,7 @@ impl ExprLowerer<'_, '_> {
             }
 
             ast::PatternKind::Bool(value) => {
-                self.unify(ty, ir::Type::bool(ast.span), ast.span);
+                self.unify(ir::Type::bool(ast.span), ty, ast.span);
 
                 ir::Pattern {
                     kind: ir::PatternKind::Bool(value),
@@ -172,7 +191,7 @@ impl ExprLowerer<'_, '_> {
             }
 
             ast::PatternKind::Int(value) => {
-                self.unify(ty, ir::Type::int(ast.span), ast.span);
+                self.unify(ir::Type::int(ast.span), ty, ast.span);
 
                 ir::Pattern {
                     kind: ir::PatternKind::Int(value),
@@ -180,8 +199,27 @@ impl ExprLowerer<'_, '_> {
                 }
             }
 
+            ast::PatternKind::Range(lower, upper) => {
+                self.unify(ir::Type::int(ast.span), ty, ast.span);
+
+                if lower > upper {
+                    let diagnostic = Diagnostic::error("empty range pattern")
+                        .with_code(code::E0035)
+                        .with_label(ast.span, format!("no integer is in `{lower}..={upper}`"))
+                        .with_help(format!("did you mean `{upper}..={lower}`?"));
+
+                    self.lowerer.emitter.emit(diagnostic);
+                    return Err(LowerError);
+                }
+
+                ir::Pattern {
+                    kind: ir::PatternKind::Range(lower, upper),
+                    span: ast.span,
+                }
+            }
+
             ast::PatternKind::String(value) => {
-                self.unify(ty, ir::Type::str(ast.span), ast.span);
+                self.unify(ir::Type::str(ast.span), ty, ast.span);
 
                 ir::Pattern {
                     kind: ir::PatternKind::String(value),
@@ -189,11 +227,22 @@ impl ExprLowerer<'_, '_> {
                 }
             }
 
+            ast::PatternKind::Prefix(prefix, rest) => {
+                self.unify(ir::Type::str(ast.span), ty.clone(), ast.span);
+
+                let rest = self.lower_pattern(*rest, ty)?;
+
+                ir::Pattern {
+                    kind: ir::PatternKind::Prefix(prefix, Box::new(rest)),
+                    span: ast.span,
+                }
+            }
+
             ast::PatternKind::List(items, rest) => {
                 let item_ty = ir::Type::infer(ast.span);
                 let list_ty = ir::Type::list(item_ty.clone(), ast.span);
 
-                self.unify(ty, list_ty.clone(), ast.span);
+                self.unify(list_ty.clone(), ty, ast.span);
 
                 let mut pattern = match rest {
                     Some(rest) => self.lower_pattern(*rest, list_ty.clone())?,
@@ -213,6 +262,193 @@ impl ExprLowerer<'_, '_> {
 
                 pattern
             }
+
+            ast::PatternKind::Record(path, fields, rest) => {
+                let (tid, record) = self.resolve_record(&path)?;
+
+                let generics = self.ir.tcx[tid]
+                    .generics
+                    .iter()
+                    .map(|(_, var)| ir::Type::infer(var.span()))
+                    .collect::<Vec<_>>();
+
+                let record_ty = ir::Type::newtype(tid, generics, path.span);
+                self.unify(record_ty.clone(), ty, ast.span);
+
+                let mut ir_fields: Vec<(String, ir::Pattern)> = Vec::new();
+
+                for (name, pattern, span) in fields {
+                    if ir_fields.iter().any(|(n, _)| *n == name) {
+                        let diagnostic = Diagnostic::error(format!(
+                            "duplicate field '{name}' in record pattern"
+                        ))
+                        .with_code(code::E0020)
+                        .with_label(span, "found here");
+
+                        self.lowerer.emitter.emit(diagnostic);
+                        return Err(LowerError);
+                    }
+
+                    let field_ty = ir::Type::infer(span);
+                    self.field(record_ty.clone(), &name, field_ty.clone(), span);
+
+                    let pattern = self.lower_pattern(pattern, field_ty)?;
+                    ir_fields.push((name, pattern));
+                }
+
+                for field in record.fields.iter() {
+                    if !rest && !ir_fields.iter().any(|(n, _)| *n == field.name) {
+                        let diagnostic = Diagnostic::error(format!(
+                            "missing field '{}' in record pattern '{}'",
+                            field.name, path
+                        ))
+                        .with_code(code::E0021)
+                        .with_label(ast.span, "found here")
+                        .with_help("use `..` to ignore the remaining fields");
+
+                        self.lowerer.emitter.emit(diagnostic);
+                        return Err(LowerError);
+                    }
+                }
+
+                ir::Pattern {
+                    kind: ir::PatternKind::Record(record_ty, ir_fields),
+                    span: ast.span,
+                }
+            }
+
+            ast::PatternKind::Or(alternatives) => {
+                let start = self.scope.len();
+
+                let mut alternatives = alternatives.into_iter();
+                let first = alternatives
+                    .next()
+                    .expect("or pattern without alternatives");
+                let first_span = first.span;
+
+                let mut patterns = vec![self.lower_pattern(first, ty.clone())?];
+                let bindings = self.scope[start..].to_vec();
+
+                for alternative in alternatives {
+                    let span = alternative.span;
+                    let mut pattern = self.lower_pattern(alternative, ty.clone())?;
+                    let locals = self.scope.split_off(start + bindings.len());
+
+                    let mut renames = HashMap::new();
+
+                    for &lid in &locals {
+                        let local = self.body().locals[lid].clone();
+
+                        let binding = bindings
+                            .iter()
+                            .find(|&&binding| self.body().locals[binding].name == local.name);
+
+                        let Some(&binding) = binding else {
+                            return Err(self.unbound_alternative(&local.name, span, first_span));
+                        };
+
+                        let found = local.ty;
+                        let expected = self.body().locals[binding].ty.clone();
+                        self.unify(found, expected, span);
+
+                        renames.insert(lid, binding);
+                    }
+
+                    for &binding in &bindings {
+                        if !renames.values().any(|&lid| lid == binding) {
+                            let name = self.body().locals[binding].name.clone();
+                            return Err(self.unbound_alternative(&name, first_span, span));
+                        }
+                    }
+
+                    rename_bindings(&mut pattern, &renames);
+                    patterns.push(pattern);
+                }
+
+                ir::Pattern {
+                    kind: ir::PatternKind::Or(patterns),
+                    span: ast.span,
+                }
+            }
+
+            ast::PatternKind::As(pattern, name, name_span) => {
+                let pattern = self.lower_pattern(*pattern, ty.clone())?;
+
+                let lid = self.body_mut().locals.push(ir::Local {
+                    name,
+                    span: name_span,
+                    ty,
+                });
+                self.scope.push(lid);
+
+                ir::Pattern {
+                    kind: ir::PatternKind::As(Box::new(pattern), lid),
+                    span: ast.span,
+                }
+            }
+
+            ast::PatternKind::Ascription(pattern, ascribed) => {
+                let ascribed = self.lower_ascribed_type(ascribed)?;
+                self.unify(ty.clone(), ascribed, ast.span);
+
+                self.lower_pattern(*pattern, ty)?
+            }
         })
     }
+
+    /// Report `name`, bound by the alternative at `bound`, as missing from
+    /// the alternative at `missing`.
+    fn unbound_alternative(&mut self, name: &str, bound: Span, missing: Span) -> LowerError {
+        let diagnostic = Diagnostic::error(format!(
+            "variable `{name}` is not bound in all alternatives"
+        ))
+        .with_code(code::E0034)
+        .with_label(missing, format!("pattern does not bind `{name}`"))
+        .with_secondary_label(bound, format!("`{name}` is bound here"));
+
+        self.lowerer.emitter.emit(diagnostic);
+        LowerError
+    }
+}
+
+/// Replace the locals bound by `pattern` according to `renames`.
+fn rename_bindings(pattern: &mut ir::Pattern, renames: &HashMap<ir::Lid, ir::Lid>) {
+    match pattern.kind {
+        ir::PatternKind::Binding(ref mut lid) => *lid = renames[lid],
+
+        ir::PatternKind::As(ref mut pattern, ref mut lid) => {
+            rename_bindings(pattern, renames);
+            *lid = renames[lid];
+        }
+
+        ir::PatternKind::Tuple(ref mut patterns) | ir::PatternKind::Or(ref mut patterns) => {
+            for pattern in patterns {
+                rename_bindings(pattern, renames);
+            }
+        }
+
+        ir::PatternKind::Record(_, ref mut fields) => {
+            for (_, pattern) in fields {
+                rename_bindings(pattern, renames);
+            }
+        }
+
+        ir::PatternKind::Variant(_, _, Some(ref mut pattern))
+        | ir::PatternKind::Prefix(_, ref mut pattern) => {
+            rename_bindings(pattern, renames);
+        }
+
+        ir::PatternKind::ListCons(ref mut head, ref mut tail) => {
+            rename_bindings(head, renames);
+            rename_bindings(tail, renames);
+        }
+
+        ir::PatternKind::Wildcard
+        | ir::PatternKind::Bool(_)
+        | ir::PatternKind::Int(_)
+        | ir::PatternKind::Range(_, _)
+        | ir::PatternKind::String(_)
+        | ir::PatternKind::Variant(_, _, None)
+        | ir::PatternKind::ListEmpty => {}
+    }
 }
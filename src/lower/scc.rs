@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use crate::ir::untyped as ir;
+
+/// The strongly connected components of the call graph, computed with
+/// Tarjan's algorithm while functions are lowered.
+///
+/// Functions are lowered depth first, lowering every function they refer to
+/// before themselves. A function that is still on the stack when it is
+/// referred to is part of the same component as the function referring to
+/// it, and must therefore be used with its type as is. Every other function
+/// belongs to a component that has been fully inferred, and its type can be
+/// generalized.
+#[derive(Debug, Default)]
+pub(super) struct Components {
+    indices: HashMap<ir::Bid, usize>,
+    lowlinks: HashMap<ir::Bid, usize>,
+    stack: Vec<ir::Bid>,
+}
+
+impl Components {
+    /// Start lowering `bid`.
+    pub(super) fn enter(&mut self, bid: ir::Bid) {
+        let index = self.indices.len();
+
+        self.indices.insert(bid, index);
+        self.lowlinks.insert(bid, index);
+        self.stack.push(bid);
+    }
+
+    /// Finish lowering `bid`, closing its component if it is the root of it,
+    /// returning the members of the closed component.
+    pub(super) fn leave(&mut self, bid: ir::Bid) -> Vec<ir::Bid> {
+        let mut members = Vec::new();
+
+        if self.lowlinks[&bid] != self.indices[&bid] {
+            return members;
+        }
+
+        while let Some(member) = self.stack.pop() {
+            members.push(member);
+
+            if member == bid {
+                break;
+            }
+        }
+
+        members
+    }
+
+    /// Record that `caller` refers to `callee`, returning whether they are in
+    /// the same component.
+    pub(super) fn refer(&mut self, caller: ir::Bid, callee: ir::Bid) -> bool {
+        if !self.stack.contains(&callee) {
+            return false;
+        }
+
+        let lowlink = self.lowlinks[&callee];
+
+        if let Some(caller) = self.lowlinks.get_mut(&caller) {
+            *caller = (*caller).min(lowlink);
+        }
+
+        true
+    }
+}
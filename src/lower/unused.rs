@@ -0,0 +1,245 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    ast,
+    attr::Attrs,
+    diagnostic::{Diagnostic, Emitter},
+    ir::untyped as ir,
+    lint::{Lint, Lints},
+};
+
+use super::Lowerer;
+
+impl Lowerer<'_> {
+    /// Mark the first segment of `path` as used in `module`, so that the
+    /// import it refers to, if any, is not reported as unused.
+    pub(super) fn use_path(&mut self, module: ir::Mid, path: &ast::Path) {
+        if let Some(segment) = path.segments().next() {
+            self.used.insert((module, segment.to_string()));
+        }
+    }
+
+    pub(super) fn lint_unused_imports(&mut self) {
+        for (module, name, span, attrs) in &self.imports {
+            if name.starts_with('_') || self.used.contains(&(*module, name.clone())) {
+                continue;
+            }
+
+            let diagnostic = Diagnostic::warn(format!("unused import: `{name}`"))
+                .with_label(*span, "imported here")
+                .with_lint(Lint::UnusedImports);
+
+            if let Some(diagnostic) = self.lints.filter(attrs, diagnostic) {
+                self.emitter.emit(diagnostic);
+            }
+        }
+    }
+
+    pub(super) fn lint_unused_locals(&mut self) {
+        let default = Attrs::default();
+
+        for (bid, body) in self.ir.bodies.iter() {
+            let attrs = self.ir.attrs.get(&bid).unwrap_or(&default);
+            let mut used = HashSet::new();
+            let mut bindings = Vec::new();
+
+            for input in &body.inputs {
+                pattern_bindings(input, &mut bindings);
+            }
+
+            if let Some(ref expr) = body.expr {
+                expr_locals(expr, &mut used, &mut bindings);
+            }
+
+            for lid in bindings {
+                let ir::Local { ref name, span, .. } = body.locals[lid];
+
+                if name.starts_with('_') || used.contains(&lid) {
+                    continue;
+                }
+
+                let diagnostic = Diagnostic::warn(format!("unused variable: `{name}`"))
+                    .with_label(span, "never used")
+                    .with_help(format!(
+                        "if this is intentional, prefix it with an underscore: `_{name}`"
+                    ))
+                    .with_lint(Lint::UnusedVariables);
+
+                if let Some(diagnostic) = self.lints.filter(attrs, diagnostic) {
+                    self.emitter.emit(diagnostic);
+                }
+            }
+        }
+    }
+}
+
+/// Warn about every function in `package` that cannot be reached from `entry`.
+///
+/// Functions in other packages are exported and are therefore never reported.
+pub fn lint_unreachable(
+    program: &ir::Program,
+    package: &str,
+    entry: ir::Bid,
+    lints: &mut Lints,
+    emitter: &mut dyn Emitter,
+) {
+    // impls are used through their trait, wherever that happens
+    let impls = program.tcx.impls().iter();
+    let impls = impls.flat_map(|r#impl| r#impl.methods.values().copied());
+
+    let mut reachable: HashSet<_> = impls.chain([entry]).collect();
+    let mut queue: VecDeque<_> = reachable.iter().copied().collect();
+
+    while let Some(caller) = queue.pop_front() {
+        for call in program.calls.iter().filter(|call| call.caller == caller) {
+            if reachable.insert(call.callee) {
+                queue.push_back(call.callee);
+            }
+        }
+    }
+
+    let prefix = format!("{package}::");
+    let default = Attrs::default();
+
+    for (bid, body) in program.bodies.iter() {
+        if reachable.contains(&bid) || body.name.ends_with("{lambda}") {
+            continue;
+        }
+
+        let Some(name) = body.name.strip_prefix(&prefix) else {
+            continue;
+        };
+
+        let unused = name
+            .rsplit("::")
+            .next()
+            .is_some_and(|name| !name.starts_with('_'));
+
+        if unused {
+            let diagnostic = Diagnostic::warn(format!("function `{name}` is never used"))
+                .with_label(body.span, "defined here")
+                .with_lint(Lint::DeadCode);
+
+            let attrs = program.attrs.get(&bid).unwrap_or(&default);
+
+            if let Some(diagnostic) = lints.filter(attrs, diagnostic) {
+                emitter.emit(diagnostic);
+            }
+        }
+    }
+}
+
+fn pattern_bindings(pattern: &ir::Pattern, bindings: &mut Vec<ir::Lid>) {
+    match pattern.kind {
+        ir::PatternKind::Binding(lid) => bindings.push(lid),
+
+        ir::PatternKind::Tuple(ref patterns) => {
+            for pattern in patterns {
+                pattern_bindings(pattern, bindings);
+            }
+        }
+
+        ir::PatternKind::Variant(_, _, Some(ref pattern))
+        | ir::PatternKind::Prefix(_, ref pattern) => {
+            pattern_bindings(pattern, bindings);
+        }
+
+        ir::PatternKind::ListCons(ref head, ref tail) => {
+            pattern_bindings(head, bindings);
+            pattern_bindings(tail, bindings);
+        }
+
+        ir::PatternKind::Record(_, ref fields) => {
+            for (_, pattern) in fields {
+                pattern_bindings(pattern, bindings);
+            }
+        }
+
+        // every alternative binds the same locals
+        ir::PatternKind::Or(ref patterns) => pattern_bindings(&patterns[0], bindings),
+
+        ir::PatternKind::As(ref inner, lid) => {
+            pattern_bindings(inner, bindings);
+            bindings.push(lid);
+        }
+
+        ir::PatternKind::Wildcard
+        | ir::PatternKind::Bool(_)
+        | ir::PatternKind::Int(_)
+        | ir::PatternKind::Range(_, _)
+        | ir::PatternKind::String(_)
+        | ir::PatternKind::Variant(_, _, None)
+        | ir::PatternKind::ListEmpty => {}
+    }
+}
+
+fn expr_locals(expr: &ir::Expr, used: &mut HashSet<ir::Lid>, bindings: &mut Vec<ir::Lid>) {
+    match expr.kind {
+        ir::ExprKind::Local(lid) => {
+            used.insert(lid);
+        }
+
+        ir::ExprKind::Let(ref pattern, ref value) => {
+            pattern_bindings(pattern, bindings);
+            expr_locals(value, used, bindings);
+        }
+
+        ir::ExprKind::Match(ref target, ref arms) => {
+            expr_locals(target, used, bindings);
+
+            for arm in arms {
+                pattern_bindings(&arm.pattern, bindings);
+
+                if let Some(ref guard) = arm.guard {
+                    expr_locals(guard, used, bindings);
+                }
+
+                expr_locals(&arm.expr, used, bindings);
+            }
+        }
+
+        ir::ExprKind::Format(ref exprs)
+        | ir::ExprKind::Tuple(ref exprs)
+        | ir::ExprKind::Block(ref exprs) => {
+            for expr in exprs {
+                expr_locals(expr, used, bindings);
+            }
+        }
+
+        ir::ExprKind::Record(ref fields) => {
+            for (_, expr) in fields {
+                expr_locals(expr, used, bindings);
+            }
+        }
+
+        ir::ExprKind::With(ref target, ref fields) => {
+            expr_locals(target, used, bindings);
+
+            for (_, expr) in fields {
+                expr_locals(expr, used, bindings);
+            }
+        }
+
+        ir::ExprKind::Variant(_, Some(ref expr))
+        | ir::ExprKind::Try(ref expr)
+        | ir::ExprKind::Unary(_, ref expr)
+        | ir::ExprKind::Field(ref expr, _) => {
+            expr_locals(expr, used, bindings);
+        }
+
+        ir::ExprKind::ListCons(ref lhs, ref rhs)
+        | ir::ExprKind::Call(ref lhs, ref rhs)
+        | ir::ExprKind::Binary(_, ref lhs, ref rhs) => {
+            expr_locals(lhs, used, bindings);
+            expr_locals(rhs, used, bindings);
+        }
+
+        ir::ExprKind::Int(_)
+        | ir::ExprKind::Bool(_)
+        | ir::ExprKind::String(_)
+        | ir::ExprKind::Body(_)
+        | ir::ExprKind::Variant(_, None)
+        | ir::ExprKind::ListEmpty
+        | ir::ExprKind::Hole => {}
+    }
+}
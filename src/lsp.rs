@@ -1,42 +1,171 @@
 use std::{
     collections::{BTreeMap, HashMap},
     error::Error,
-    fs,
+    fs, iter,
     path::{Path, PathBuf},
+    rc::Rc,
     str::FromStr,
 };
 
 use lsp_server::{Connection, IoThreads, Message, Notification, RequestId, Response};
 use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
     DiagnosticSeverity, DidChangeTextDocumentParams, DidOpenTextDocumentParams, InitializeParams,
-    Position, PositionEncodingKind, PublishDiagnosticsParams, Range, SaveOptions, SemanticToken,
-    SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensFullOptions,
-    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensParams, ServerCapabilities,
-    TextDocumentSyncKind, TextDocumentSyncOptions, Uri,
+    InitializeResult, NumberOrString, Position, PositionEncodingKind, PublishDiagnosticsParams,
+    Range, SaveOptions, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions, SemanticTokensParams,
+    ServerCapabilities, SymbolKind, TextDocumentSyncKind, TextDocumentSyncOptions, Uri,
     notification::{
         DidChangeTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics,
     },
-    request::{Request, SemanticTokensFullRequest},
+    request::{
+        CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare, Request,
+        SemanticTokensFullRequest,
+    },
 };
 
 use crate::{
     ast,
     diagnostic::{Diagnostic, Level, Sid, Source, Sources, Span},
+    ir::untyped as ir,
+    lower,
     parse::{self, TokenStream},
 };
 
-#[allow(dead_code)]
 struct BuildOptions {
     workspace_folder: PathBuf,
+
+    /// The package being built, either a directory or a single file.
+    package: PathBuf,
+}
+
+impl BuildOptions {
+    fn packages(&self) -> Vec<PathBuf> {
+        let std = self.workspace_folder.join("std");
+
+        match self.package == std {
+            true => vec![std],
+            false => vec![std, self.package.clone()],
+        }
+    }
+}
+
+/// The encoding used for the `character` offset of a [`Position`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionEncoding {
+    Utf8,
+    Utf16,
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Pick the encoding to use from the encodings supported by the client.
+    ///
+    /// UTF-8 is preferred since it matches the byte offsets of a [`Span`]. If the client does not
+    /// advertise any encodings, UTF-16 is used as mandated by the specification.
+    fn negotiate(params: &InitializeParams) -> Self {
+        let encodings = params
+            .capabilities
+            .general
+            .as_ref()
+            .and_then(|general| general.position_encodings.as_deref())
+            .unwrap_or_default();
+
+        if encodings.contains(&PositionEncodingKind::UTF8) {
+            Self::Utf8
+        } else if encodings.contains(&PositionEncodingKind::UTF32) {
+            Self::Utf32
+        } else {
+            Self::Utf16
+        }
+    }
+
+    fn kind(self) -> PositionEncodingKind {
+        match self {
+            Self::Utf8 => PositionEncodingKind::UTF8,
+            Self::Utf16 => PositionEncodingKind::UTF16,
+            Self::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    fn len(self, c: char) -> u32 {
+        match self {
+            Self::Utf8 => c.len_utf8() as u32,
+            Self::Utf16 => c.len_utf16() as u32,
+            Self::Utf32 => 1,
+        }
+    }
+
+    /// Compute the [`Position`] of a byte `offset` into `source`, whose lines start at the byte
+    /// offsets in `lines`.
+    fn position(self, source: &str, lines: &[u32], offset: u32) -> Position {
+        let line = lines
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1);
+        let start = lines.get(line).copied().unwrap_or(0) as usize;
+
+        // offsets past the end, or inside a character, are moved back onto one
+        let mut end = (offset as usize).clamp(start, source.len());
+
+        while !source.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let text = &source[start..end];
+
+        Position {
+            line: line as u32,
+            character: self.text_len(text),
+        }
+    }
+
+    /// Compute the byte offset of a [`Position`] in `source`, whose lines start at the byte
+    /// offsets in `lines`.
+    fn offset(self, source: &str, lines: &[u32], position: Position) -> u32 {
+        let Some(&start) = lines.get(position.line as usize) else {
+            return source.len() as u32;
+        };
+
+        let mut character = 0;
+
+        for (index, c) in source[start as usize..].char_indices() {
+            if character >= position.character || c == '\n' {
+                return start + index as u32;
+            }
+
+            character += self.len(c);
+        }
+
+        source.len() as u32
+    }
+
+    /// Compute the byte offsets at which the lines of `source` start.
+    fn lines(source: &str) -> Vec<u32> {
+        let newlines = source
+            .match_indices('\n')
+            .map(|(index, _)| index as u32 + 1);
+
+        iter::once(0).chain(newlines).collect()
+    }
+
+    /// Compute the length of `text` in code units.
+    fn text_len(self, text: &str) -> u32 {
+        text.chars().map(|c| self.len(c)).sum()
+    }
 }
 
 pub struct LanguageServer {
     pub connection: Connection,
     pub io_threads: IoThreads,
     pub params: InitializeParams,
+    pub encoding: PositionEncoding,
 
     pub sources: Sources,
 
+    /// The byte offset of the start of every line, by source.
+    pub lines: HashMap<Sid, Vec<u32>>,
+
     /// A map from absolute paths to sources.
     pub sids: HashMap<PathBuf, Sid>,
 
@@ -44,12 +173,15 @@ pub struct LanguageServer {
 
     pub tokens: HashMap<Sid, TokenStream>,
     pub asts: HashMap<Sid, ast::File>,
+
+    /// Lowered programs by package path, cleared whenever a document changes.
+    pub programs: HashMap<PathBuf, Rc<ir::Program>>,
 }
 
 impl LanguageServer {
-    fn capabilities() -> ServerCapabilities {
+    fn capabilities(encoding: PositionEncoding) -> ServerCapabilities {
         ServerCapabilities {
-            position_encoding: Some(PositionEncodingKind::UTF8),
+            position_encoding: Some(encoding.kind()),
             text_document_sync: Some(
                 TextDocumentSyncOptions {
                     open_close: Some(true),
@@ -77,6 +209,7 @@ impl LanguageServer {
                 }
                 .into(),
             ),
+            call_hierarchy_provider: Some(true.into()),
             ..Default::default()
         }
     }
@@ -84,18 +217,31 @@ impl LanguageServer {
     pub fn new() -> Result<Self, Box<dyn Error>> {
         let (connection, io_threads) = Connection::stdio();
 
-        let params = connection.initialize(serde_json::to_value(Self::capabilities())?)?;
+        let (id, params) = connection.initialize_start()?;
         let params: InitializeParams = serde_json::from_value(params)?;
 
+        let encoding = PositionEncoding::negotiate(&params);
+
+        let result = InitializeResult {
+            capabilities: Self::capabilities(encoding),
+            server_info: None,
+            offset_encoding: None,
+        };
+
+        connection.initialize_finish(id, serde_json::to_value(result)?)?;
+
         Ok(Self {
             connection,
             io_threads,
             params,
+            encoding,
             sources: Sources::new(),
+            lines: HashMap::new(),
             sids: HashMap::new(),
             diagnostics: HashMap::new(),
             tokens: HashMap::new(),
             asts: HashMap::new(),
+            programs: HashMap::new(),
         })
     }
 
@@ -107,9 +253,32 @@ impl LanguageServer {
                         break;
                     }
 
-                    if request.method == SemanticTokensFullRequest::METHOD {
-                        let params: SemanticTokensParams = serde_json::from_value(request.params)?;
-                        self.handle_token_semantics(params, request.id)?
+                    match request.method.as_str() {
+                        SemanticTokensFullRequest::METHOD => {
+                            let params: SemanticTokensParams =
+                                serde_json::from_value(request.params)?;
+                            self.handle_token_semantics(params, request.id)?
+                        }
+
+                        CallHierarchyPrepare::METHOD => {
+                            let params: CallHierarchyPrepareParams =
+                                serde_json::from_value(request.params)?;
+                            self.handle_call_hierarchy_prepare(params, request.id)?
+                        }
+
+                        CallHierarchyIncomingCalls::METHOD => {
+                            let params: CallHierarchyIncomingCallsParams =
+                                serde_json::from_value(request.params)?;
+                            self.handle_incoming_calls(params, request.id)?
+                        }
+
+                        CallHierarchyOutgoingCalls::METHOD => {
+                            let params: CallHierarchyOutgoingCallsParams =
+                                serde_json::from_value(request.params)?;
+                            self.handle_outgoing_calls(params, request.id)?
+                        }
+
+                        _ => {}
                     }
                 }
 
@@ -123,6 +292,7 @@ impl LanguageServer {
                         let path = Path::new(params.text_document.uri.path().as_str());
                         let content = params.content_changes.into_iter().next().unwrap().text;
                         self.content_changed(path, content);
+                        self.programs.clear();
 
                         self.publish_diagnostics()?;
                     }
@@ -134,6 +304,7 @@ impl LanguageServer {
                         let path = Path::new(params.text_document.uri.path().as_str());
                         let content = fs::read_to_string(path)?;
                         self.content_changed(path, content);
+                        self.programs.clear();
 
                         self.publish_diagnostics()?;
                     }
@@ -158,15 +329,15 @@ impl LanguageServer {
         for diagnostic in self.diagnostics.values().flatten() {
             for label in &diagnostic.labels {
                 let source = &self.sources[label.span.id];
-                let (start_line, start_column) =
-                    label.span.compute_start_line_column(&source.content);
-                let (end_line, end_column) = label.span.compute_end_line_column(&source.content);
 
                 diagnostics
                     .entry(source.path.clone())
                     .or_default()
                     .push(lsp_types::Diagnostic {
                         message: diagnostic.message.clone(),
+                        code: diagnostic
+                            .code
+                            .map(|code| NumberOrString::String(code.to_string())),
 
                         severity: match diagnostic.level {
                             Level::Error => Some(DiagnosticSeverity::ERROR),
@@ -174,16 +345,7 @@ impl LanguageServer {
                             Level::Note => Some(DiagnosticSeverity::INFORMATION),
                         },
 
-                        range: Range {
-                            start: Position {
-                                line: start_line - 1,
-                                character: start_column - 1,
-                            },
-                            end: Position {
-                                line: end_line - 1,
-                                character: end_column - 1,
-                            },
-                        },
+                        range: self.range(label.span),
                         ..Default::default()
                     });
             }
@@ -191,7 +353,7 @@ impl LanguageServer {
 
         for (path, diagnostics) in diagnostics {
             let params = PublishDiagnosticsParams {
-                uri: Uri::from_str(&format!("file://{}", path.display())).unwrap(),
+                uri: path_uri(&path),
                 diagnostics,
                 version: None,
             };
@@ -207,10 +369,32 @@ impl LanguageServer {
         Ok(())
     }
 
+    fn position(&self, sid: Sid, offset: u32) -> Position {
+        let lines = self.lines.get(&sid).map_or(&[][..], Vec::as_slice);
+        self.encoding
+            .position(&self.sources[sid].content, lines, offset)
+    }
+
+    fn range(&self, span: Span) -> Range {
+        Range {
+            start: self.position(span.id, span.lo),
+            end: self.position(span.id, span.hi),
+        }
+    }
+
+    fn offset(&self, sid: Sid, position: Position) -> u32 {
+        let lines = self.lines.get(&sid).map_or(&[][..], Vec::as_slice);
+        self.encoding
+            .offset(&self.sources[sid].content, lines, position)
+    }
+
     fn content_changed(&mut self, path: &Path, content: String) {
+        let lines = PositionEncoding::lines(&content);
+
         match self.sids.get(path).copied() {
             Some(sid) => {
                 self.sources[sid].content = content;
+                self.lines.insert(sid, lines);
                 self.tokens.remove(&sid);
                 self.asts.remove(&sid);
                 self.diagnostics.get_mut(&sid).map(Vec::clear);
@@ -225,6 +409,7 @@ impl LanguageServer {
                 });
 
                 self.sids.insert(path.to_path_buf(), sid);
+                self.lines.insert(sid, lines);
                 self.tokenize(sid);
                 self.parse(sid);
             }
@@ -265,6 +450,244 @@ impl LanguageServer {
         self.asts.insert(sid, file);
     }
 
+    fn workspace_folder(&self) -> Option<PathBuf> {
+        let folders = self.params.workspace_folders.as_deref().unwrap_or_default();
+
+        if let Some(folder) = folders.first() {
+            return Some(PathBuf::from(folder.uri.path().as_str()));
+        }
+
+        #[allow(deprecated)]
+        let root = self.params.root_uri.as_ref()?;
+        Some(PathBuf::from(root.path().as_str()))
+    }
+
+    fn build_options(&self, path: &Path) -> Option<BuildOptions> {
+        let workspace_folder = self.workspace_folder()?;
+        let relative = path.strip_prefix(&workspace_folder).ok()?;
+        let package = workspace_folder.join(relative.components().next()?);
+
+        Some(BuildOptions {
+            workspace_folder,
+            package,
+        })
+    }
+
+    fn load_file(&mut self, path: &Path) -> Option<ast::File> {
+        if !self.sids.contains_key(path) {
+            let content = fs::read_to_string(path).ok()?;
+            self.content_changed(path, content);
+        }
+
+        self.asts.get(&self.sids[path]).cloned()
+    }
+
+    fn load_module(&mut self, path: &Path) -> ast::Module {
+        let mut module = ast::Module::new();
+
+        let Ok(entries) = fs::read_dir(path) else {
+            return module;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if path.is_dir() {
+                let submodule = self.load_module(&path);
+                module.modules.insert(name, submodule);
+            } else if path.extension().and_then(|s| s.to_str()) == Some("ike")
+                && let Some(file) = self.load_file(&path)
+            {
+                module.files.insert(name, file);
+            }
+        }
+
+        module
+    }
+
+    /// Lower the package containing `path` together with the standard library.
+    fn lower(&mut self, path: &Path) -> Option<Rc<ir::Program>> {
+        let options = self.build_options(path)?;
+
+        if let Some(program) = self.programs.get(&options.package) {
+            return Some(program.clone());
+        }
+
+        let mut modules = Vec::new();
+
+        for package in options.packages() {
+            let name = package.file_stem()?.to_string_lossy().to_string();
+
+            let module = match package.is_dir() {
+                true => self.load_module(&package),
+                false => {
+                    let file = self.load_file(&package)?;
+
+                    let mut module = ast::Module::new();
+                    module.files.insert(name.clone(), file);
+
+                    module
+                }
+            };
+
+            modules.push((name, module));
+        }
+
+        let mut emitter = Vec::new();
+        let mut lowerer = lower::Lowerer::new(&mut emitter);
+
+        // errors are expected while editing, so a partially lowered program is
+        // used rather than none at all
+        for (name, module) in &modules {
+            let _ = lowerer.add_module(&[name], module);
+        }
+
+        let (program, _) = lowerer.finish();
+        let program = Rc::new(program);
+        self.programs.insert(options.package, program.clone());
+
+        Some(program)
+    }
+
+    /// Find the function defined or called at `offset`.
+    fn find_function(program: &ir::Program, sid: Sid, offset: u32) -> Option<ir::Bid> {
+        let contains = |span: Span| span.id == sid && span.lo <= offset && offset <= span.hi;
+
+        if let Some(call) = program.calls.iter().find(|call| contains(call.span)) {
+            return Some(call.callee);
+        }
+
+        program
+            .bodies
+            .iter()
+            .filter(|(_, body)| !body.name.ends_with("{lambda}"))
+            .find(|(_, body)| contains(body.span))
+            .map(|(bid, _)| bid)
+    }
+
+    fn call_hierarchy_item(&self, program: &ir::Program, bid: ir::Bid) -> CallHierarchyItem {
+        let body = &program[bid];
+        let range = self.range(body.span);
+
+        CallHierarchyItem {
+            name: body.name.clone(),
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            detail: Some(program.tcx.format_type(&body.ty)),
+            uri: path_uri(&self.sources[body.span.id].path),
+            range,
+            selection_range: range,
+            data: None,
+        }
+    }
+
+    /// Resolve a [`CallHierarchyItem`] previously returned to the client.
+    fn resolve_call_hierarchy_item(
+        &mut self,
+        item: &CallHierarchyItem,
+    ) -> Option<(Rc<ir::Program>, ir::Bid)> {
+        let path = PathBuf::from(item.uri.path().as_str());
+        let program = self.lower(&path)?;
+
+        let sid = *self.sids.get(&path)?;
+        let offset = self.offset(sid, item.selection_range.start);
+
+        let (bid, _) = (program.bodies.iter()).find(|(_, body)| {
+            body.span.id == sid && body.span.lo == offset //
+        })?;
+
+        Some((program, bid))
+    }
+
+    fn handle_call_hierarchy_prepare(
+        &mut self,
+        params: CallHierarchyPrepareParams,
+        request_id: RequestId,
+    ) -> Result<(), Box<dyn Error>> {
+        let params = params.text_document_position_params;
+        let path = PathBuf::from(params.text_document.uri.path().as_str());
+
+        let items = self.lower(&path).and_then(|program| {
+            let sid = *self.sids.get(&path)?;
+            let offset = self.offset(sid, params.position);
+            let bid = Self::find_function(&program, sid, offset)?;
+
+            Some(vec![self.call_hierarchy_item(&program, bid)])
+        });
+
+        let response = Response::new_ok(request_id, items);
+        self.connection.sender.send(Message::Response(response))?;
+
+        Ok(())
+    }
+
+    fn handle_incoming_calls(
+        &mut self,
+        params: CallHierarchyIncomingCallsParams,
+        request_id: RequestId,
+    ) -> Result<(), Box<dyn Error>> {
+        let calls = self
+            .resolve_call_hierarchy_item(&params.item)
+            .map(|(program, bid)| {
+                let mut calls: Vec<(ir::Bid, Vec<Range>)> = Vec::new();
+
+                for call in program.calls.iter().filter(|call| call.callee == bid) {
+                    let range = self.range(call.span);
+
+                    match calls.iter_mut().find(|(caller, _)| *caller == call.caller) {
+                        Some((_, ranges)) => ranges.push(range),
+                        None => calls.push((call.caller, vec![range])),
+                    }
+                }
+
+                (calls.into_iter())
+                    .map(|(caller, from_ranges)| CallHierarchyIncomingCall {
+                        from: self.call_hierarchy_item(&program, caller),
+                        from_ranges,
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+        let response = Response::new_ok(request_id, calls);
+        self.connection.sender.send(Message::Response(response))?;
+
+        Ok(())
+    }
+
+    fn handle_outgoing_calls(
+        &mut self,
+        params: CallHierarchyOutgoingCallsParams,
+        request_id: RequestId,
+    ) -> Result<(), Box<dyn Error>> {
+        let calls = self
+            .resolve_call_hierarchy_item(&params.item)
+            .map(|(program, bid)| {
+                let mut calls: Vec<(ir::Bid, Vec<Range>)> = Vec::new();
+
+                for call in program.calls.iter().filter(|call| call.caller == bid) {
+                    let range = self.range(call.span);
+
+                    match calls.iter_mut().find(|(callee, _)| *callee == call.callee) {
+                        Some((_, ranges)) => ranges.push(range),
+                        None => calls.push((call.callee, vec![range])),
+                    }
+                }
+
+                (calls.into_iter())
+                    .map(|(callee, from_ranges)| CallHierarchyOutgoingCall {
+                        to: self.call_hierarchy_item(&program, callee),
+                        from_ranges,
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+        let response = Response::new_ok(request_id, calls);
+        self.connection.sender.send(Message::Response(response))?;
+
+        Ok(())
+    }
+
     fn handle_token_semantics(
         &self,
         params: SemanticTokensParams,
@@ -287,26 +710,25 @@ impl LanguageServer {
 
         let mut data = Vec::new();
 
-        let mut prev_line = 1;
-        let mut prev_column = 1;
+        let mut prev = Position::default();
 
         for (span, token_type, m) in semantics.semantics.into_values() {
-            let (line, column) = span.compute_start_line_column(content);
+            let start = self.position(sid, span.lo);
+            let text = &content[span.lo as usize..span.hi as usize];
 
-            if line != prev_line {
-                prev_column = 1;
+            if start.line != prev.line {
+                prev.character = 0;
             }
 
             data.push(SemanticToken {
-                delta_line: line - prev_line,
-                delta_start: column - prev_column,
-                length: span.hi - span.lo,
+                delta_line: start.line - prev.line,
+                delta_start: start.character - prev.character,
+                length: self.encoding.text_len(text),
                 token_type,
                 token_modifiers_bitset: m,
             });
 
-            prev_line = line;
-            prev_column = column;
+            prev = start;
         }
 
         let result = SemanticTokens {
@@ -391,6 +813,37 @@ impl LanguageServer {
                     Self::add_ast_fn_name_semantics(&ast.name, semantics);
                     Self::add_ast_type_semantics(&ast.ty, semantics);
                 }
+
+                ast::Item::Trait(ast) => {
+                    semantics.add(ast.span, KEYWORD, 0);
+
+                    Self::add_ast_type_name_semantics(&ast.name, semantics);
+                    semantics.add(ast.generic.1, PROPERTY, 0);
+
+                    for method in &ast.methods {
+                        semantics.add(method.span, KEYWORD, 0);
+
+                        Self::add_ast_fn_name_semantics(&method.name, semantics);
+                        Self::add_ast_type_semantics(&method.ty, semantics);
+                    }
+                }
+
+                ast::Item::Impl(ast) => {
+                    semantics.add(ast.span, KEYWORD, 0);
+
+                    Self::add_ast_type_name_semantics(&ast.r#trait, semantics);
+                    Self::add_ast_type_semantics(&ast.ty, semantics);
+
+                    for function in &ast.functions {
+                        semantics.add(function.span, KEYWORD, 0);
+
+                        Self::add_ast_fn_name_semantics(&function.name, semantics);
+
+                        if let Some(ref body) = function.body {
+                            Self::add_ast_expr_semantics(body, semantics);
+                        }
+                    }
+                }
             }
         }
     }
@@ -499,12 +952,45 @@ impl LanguageServer {
                     Self::add_ast_pattern_semantics(pattern, semantics);
                 }
             }
+
+            ast::PatternKind::Range(_, _) => {}
+
+            ast::PatternKind::Prefix(_, pattern) => {
+                Self::add_ast_pattern_semantics(pattern, semantics);
+            }
+
+            ast::PatternKind::Record(path, fields, _) => {
+                Self::add_ast_type_name_semantics(path, semantics);
+
+                for (_, pattern, span) in fields {
+                    Self::add_ast_pattern_semantics(pattern, semantics);
+                    semantics.add(*span, PROPERTY, 0);
+                }
+            }
+
+            ast::PatternKind::Or(patterns) => {
+                for pattern in patterns {
+                    Self::add_ast_pattern_semantics(pattern, semantics);
+                }
+            }
+
+            ast::PatternKind::As(pattern, _, _) => {
+                Self::add_ast_pattern_semantics(pattern, semantics);
+            }
+
+            ast::PatternKind::Ascription(pattern, ty) => {
+                Self::add_ast_pattern_semantics(pattern, semantics);
+                Self::add_ast_type_semantics(ty, semantics);
+            }
         }
     }
 
     fn add_ast_expr_semantics(ast: &ast::Expr, semantics: &mut Semantics) {
         match &ast.kind {
-            ast::ExprKind::Int(_) | ast::ExprKind::Bool(_) | ast::ExprKind::String(_) => {}
+            ast::ExprKind::Int(_)
+            | ast::ExprKind::Bool(_)
+            | ast::ExprKind::String(_)
+            | ast::ExprKind::Hole(_) => {}
 
             ast::ExprKind::Format(parts) => {
                 for part in parts {
@@ -553,7 +1039,11 @@ impl LanguageServer {
                 }
             }
 
-            ast::ExprKind::Lambda(_, body) => {
+            ast::ExprKind::Lambda(params, body) => {
+                for param in params {
+                    Self::add_ast_pattern_semantics(param, semantics);
+                }
+
                 Self::add_ast_expr_semantics(body, semantics);
             }
 
@@ -562,7 +1052,7 @@ impl LanguageServer {
                 Self::add_ast_expr_semantics(rhs, semantics);
             }
 
-            ast::ExprKind::Try(value) => {
+            ast::ExprKind::Unary(_, _, value) | ast::ExprKind::Try(value) => {
                 Self::add_ast_expr_semantics(value, semantics);
             }
 
@@ -586,19 +1076,42 @@ impl LanguageServer {
 
                 for arm in arms {
                     Self::add_ast_pattern_semantics(&arm.pattern, semantics);
+
+                    if let Some(ref guard) = arm.guard {
+                        Self::add_ast_expr_semantics(guard, semantics);
+                    }
+
                     Self::add_ast_expr_semantics(&arm.expr, semantics);
                 }
             }
 
+            ast::ExprKind::If(condition, then, r#else) => {
+                Self::add_ast_expr_semantics(condition, semantics);
+                Self::add_ast_expr_semantics(then, semantics);
+
+                if let Some(r#else) = r#else {
+                    Self::add_ast_expr_semantics(r#else, semantics);
+                }
+            }
+
             ast::ExprKind::Block(exprs) => {
                 for expr in exprs {
                     Self::add_ast_expr_semantics(expr, semantics);
                 }
             }
+
+            ast::ExprKind::Ascription(expr, ty) => {
+                Self::add_ast_expr_semantics(expr, semantics);
+                Self::add_ast_type_semantics(ty, semantics);
+            }
         }
     }
 }
 
+fn path_uri(path: &Path) -> Uri {
+    Uri::from_str(&format!("file://{}", path.display())).unwrap()
+}
+
 #[derive(Default)]
 struct Semantics {
     semantics: BTreeMap<u32, (Span, u32, u32)>,
@@ -653,7 +1166,11 @@ fn token_semantics(token: &parse::Token) -> Option<[u32; 2]> {
         parse::Token::Whitespace | parse::Token::Newline | parse::Token::Eof => return None,
 
         parse::Token::And
+        | parse::Token::As
+        | parse::Token::Else
+        | parse::Token::If
         | parse::Token::Let
+        | parse::Token::Not
         | parse::Token::Or
         | parse::Token::Match
         | parse::Token::Try
@@ -662,7 +1179,8 @@ fn token_semantics(token: &parse::Token) -> Option<[u32; 2]> {
         parse::Token::False | parse::Token::True => [ENUM_MEMBER, STATIC],
         parse::Token::Bool | parse::Token::Int | parse::Token::Str => [TYPE, 0],
 
-        parse::Token::DotDot
+        parse::Token::DotDotEq
+        | parse::Token::DotDot
         | parse::Token::RArrow
         | parse::Token::LArrow
         | parse::Token::EqEq
@@ -673,6 +1191,7 @@ fn token_semantics(token: &parse::Token) -> Option<[u32; 2]> {
         | parse::Token::PipeGt
         | parse::Token::LtLt
         | parse::Token::GtGt
+        | parse::Token::PlusPlus
         | parse::Token::Semi
         | parse::Token::Colon
         | parse::Token::Comma
@@ -705,3 +1224,107 @@ fn token_semantics(token: &parse::Token) -> Option<[u32; 2]> {
         parse::Token::ColonColon => [NAMESPACE, 0],
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use super::PositionEncoding;
+
+    const SOURCE: &str = "let é = 1\nlet 🦀 = é\n";
+
+    fn position(encoding: PositionEncoding, offset: u32) -> Position {
+        let lines = PositionEncoding::lines(SOURCE);
+        encoding.position(SOURCE, &lines, offset)
+    }
+
+    fn offset(encoding: PositionEncoding, line: u32, character: u32) -> u32 {
+        let lines = PositionEncoding::lines(SOURCE);
+        encoding.offset(SOURCE, &lines, Position { line, character })
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(PositionEncoding::lines(SOURCE), [0, 11, 25]);
+        assert_eq!(PositionEncoding::lines(""), [0]);
+    }
+
+    #[test]
+    fn position_after_multi_byte_characters() {
+        // `=` follows `é` on the first line, and `🦀` on the second
+        assert_eq!(position(PositionEncoding::Utf8, 7), Position::new(0, 7));
+        assert_eq!(position(PositionEncoding::Utf16, 7), Position::new(0, 6));
+        assert_eq!(position(PositionEncoding::Utf32, 7), Position::new(0, 6));
+
+        assert_eq!(position(PositionEncoding::Utf8, 20), Position::new(1, 9));
+        assert_eq!(position(PositionEncoding::Utf16, 20), Position::new(1, 7));
+        assert_eq!(position(PositionEncoding::Utf32, 20), Position::new(1, 6));
+    }
+
+    #[test]
+    fn position_at_end_of_line() {
+        assert_eq!(position(PositionEncoding::Utf8, 10), Position::new(0, 10));
+        assert_eq!(position(PositionEncoding::Utf16, 10), Position::new(0, 9));
+
+        assert_eq!(position(PositionEncoding::Utf8, 24), Position::new(1, 13));
+        assert_eq!(position(PositionEncoding::Utf16, 24), Position::new(1, 10));
+        assert_eq!(position(PositionEncoding::Utf32, 24), Position::new(1, 9));
+    }
+
+    #[test]
+    fn position_past_end() {
+        assert_eq!(position(PositionEncoding::Utf16, 25), Position::new(2, 0));
+        assert_eq!(position(PositionEncoding::Utf16, 100), Position::new(2, 0));
+    }
+
+    #[test]
+    fn position_inside_character() {
+        // the second byte of `é` is moved back to its start
+        assert_eq!(position(PositionEncoding::Utf16, 5), Position::new(0, 4));
+        assert_eq!(position(PositionEncoding::Utf16, 17), Position::new(1, 4));
+    }
+
+    #[test]
+    fn offset_after_multi_byte_characters() {
+        assert_eq!(offset(PositionEncoding::Utf8, 0, 7), 7);
+        assert_eq!(offset(PositionEncoding::Utf16, 0, 6), 7);
+        assert_eq!(offset(PositionEncoding::Utf32, 0, 6), 7);
+
+        assert_eq!(offset(PositionEncoding::Utf8, 1, 9), 20);
+        assert_eq!(offset(PositionEncoding::Utf16, 1, 7), 20);
+        assert_eq!(offset(PositionEncoding::Utf32, 1, 6), 20);
+    }
+
+    #[test]
+    fn offset_past_end_of_line() {
+        assert_eq!(offset(PositionEncoding::Utf16, 0, 9), 10);
+        assert_eq!(offset(PositionEncoding::Utf16, 0, 100), 10);
+        assert_eq!(offset(PositionEncoding::Utf32, 1, 100), 24);
+    }
+
+    #[test]
+    fn offset_past_end() {
+        assert_eq!(offset(PositionEncoding::Utf16, 2, 0), 25);
+        assert_eq!(offset(PositionEncoding::Utf16, 2, 5), 25);
+        assert_eq!(offset(PositionEncoding::Utf16, 7, 0), 25);
+    }
+
+    #[test]
+    fn round_trip() {
+        let encodings = [
+            PositionEncoding::Utf8,
+            PositionEncoding::Utf16,
+            PositionEncoding::Utf32,
+        ];
+
+        for encoding in encodings {
+            for (offset, _) in SOURCE.char_indices() {
+                let offset = offset as u32;
+                let position = position(encoding, offset);
+                let lines = PositionEncoding::lines(SOURCE);
+
+                assert_eq!(encoding.offset(SOURCE, &lines, position), offset);
+            }
+        }
+    }
+}
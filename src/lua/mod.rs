@@ -1,10 +1,18 @@
 use std::io::{self, Write};
 
-use crate::ir::typed as ir;
+use crate::{
+    diagnostic::{Sources, Span},
+    ir::typed as ir,
+};
 
 const PRELUDE: &str = include_str!("prelude.lua");
 
-pub fn codegen(f: &mut dyn Write, ir: &ir::Program, entry: ir::Bid) -> io::Result<()> {
+pub fn codegen(
+    f: &mut dyn Write,
+    ir: &ir::Program,
+    sources: &Sources,
+    entry: ir::Bid,
+) -> io::Result<()> {
     writeln!(f, "{PRELUDE}")?;
 
     for (bid, body) in ir.bodies.iter() {
@@ -24,6 +32,8 @@ pub fn codegen(f: &mut dyn Write, ir: &ir::Program, entry: ir::Bid) -> io::Resul
         }
 
         let mut codegen = Codegen {
+            ir,
+            sources,
             body: String::new(),
             indent: 4,
             temp: 0,
@@ -53,18 +63,32 @@ pub fn codegen(f: &mut dyn Write, ir: &ir::Program, entry: ir::Bid) -> io::Resul
         writeln!(f, "end\n")?;
     }
 
+    let mut methods = ir.methods.iter().collect::<Vec<_>>();
+    methods.sort_by_key(|(tid, _)| tid.index());
+
+    for (tid, methods) in methods {
+        let methods = methods
+            .iter()
+            .map(|(name, bid)| format!("[\"{name}\"] = M[\"body{}\"]()", bid.index()))
+            .collect::<Vec<_>>();
+
+        writeln!(f, "T[{}] = {{ {} }}", tid.index(), methods.join(", "))?;
+    }
+
     writeln!(f, "M[\"body{}\"]()", entry.index(),)?;
 
     Ok(())
 }
 
-struct Codegen {
+struct Codegen<'a> {
+    ir: &'a ir::Program,
+    sources: &'a Sources,
     body: String,
     indent: usize,
     temp: usize,
 }
 
-impl Codegen {
+impl Codegen<'_> {
     fn line(&mut self, line: impl AsRef<str>) {
         self.body += &" ".repeat(self.indent);
         self.body += line.as_ref();
@@ -81,6 +105,26 @@ impl Codegen {
         }
     }
 
+    /// The location of `span` in its source, as reported by runtime panics,
+    /// quoted as a Lua string literal.
+    fn location(&self, span: Span) -> String {
+        let source = &self.sources[span.id];
+        let (line, column) = span.compute_start_line_column(&source.content);
+        quote(&format!("{}:{line}:{column}", source.path.display()))
+    }
+
+    /// Give a newly constructed `value` of type `ty` the methods of its
+    /// type, if it has any.
+    fn methods(&self, value: String, ty: &ir::Type) -> String {
+        match ty {
+            ir::Type::Newtype(tid, _) if self.ir.methods.contains_key(tid) => {
+                format!("setmetatable({value}, T[{}])", tid.index())
+            }
+
+            _ => value,
+        }
+    }
+
     fn expr(&mut self, expr: &ir::Expr) -> String {
         match &expr.kind {
             ir::ExprKind::Int(value) => format!("{value}"),
@@ -102,15 +146,19 @@ impl Codegen {
                 String::from("nil")
             }
 
-            ir::ExprKind::Variant(name, None) => format!("{{ tag = \"{name}\" }}"),
+            ir::ExprKind::Variant(name, None) => {
+                self.methods(format!("{{ tag = \"{name}\" }}"), &expr.ty)
+            }
 
-            ir::ExprKind::Variant(name, Some(expr)) => {
-                let value = self.expr(expr);
-                format!("{{ tag = \"{name}\", value = {value} }}")
+            ir::ExprKind::Variant(name, Some(value)) => {
+                let value = self.expr(value);
+                self.methods(format!("{{ tag = \"{name}\", value = {value} }}"), &expr.ty)
             }
 
             ir::ExprKind::ListEmpty => String::from("{ __list = true }"),
 
+            ir::ExprKind::Hole => unreachable!("holes are rejected during lowering"),
+
             ir::ExprKind::ListCons(head, tail) => {
                 let head_value = self.expr(head);
                 let tail_value = self.expr(tail);
@@ -128,7 +176,7 @@ impl Codegen {
                     .map(|(name, expr)| format!("[\"{}\"] = {}", name, self.expr(expr)))
                     .collect::<Vec<_>>();
 
-                format!("{{ {} }}", fields.join(", "))
+                self.methods(format!("{{ {} }}", fields.join(", ")), &expr.ty)
             }
 
             ir::ExprKind::With(target, fields) => {
@@ -162,6 +210,16 @@ impl Codegen {
                 format!("({callee})({input})")
             }
 
+            ir::ExprKind::Unary(op, value) => {
+                let value = self.expr(value);
+
+                match op {
+                    ir::UnOp::Neg => format!("(-{value})"),
+                    ir::UnOp::Not => format!("(not {value})"),
+                    ir::UnOp::BitNot => format!("(~{value})"),
+                }
+            }
+
             ir::ExprKind::Binary(op, lhs, rhs) => {
                 let lhs = self.expr(lhs);
                 let rhs = self.expr(rhs);
@@ -170,10 +228,21 @@ impl Codegen {
                     ir::BinOp::Add => "+",
                     ir::BinOp::Sub => "-",
                     ir::BinOp::Mul => "*",
-                    ir::BinOp::Div => "/",
-                    ir::BinOp::Mod => "%",
+                    ir::BinOp::Div => {
+                        let location = self.location(expr.span);
+                        return format!("div({lhs}, {rhs}, {location})");
+                    }
+
+                    ir::BinOp::Mod => {
+                        let location = self.location(expr.span);
+                        return format!("mod({lhs}, {rhs}, {location})");
+                    }
+
                     ir::BinOp::Shl => "<<",
                     ir::BinOp::Shr => ">>",
+                    ir::BinOp::BitAnd => "&",
+                    ir::BinOp::BitOr => "|",
+                    ir::BinOp::BitXor => "~",
                     ir::BinOp::And => "and",
                     ir::BinOp::Or => "or",
                     ir::BinOp::Eq => return format!("equal({lhs}, {rhs})"),
@@ -196,21 +265,39 @@ impl Codegen {
                 self.line(format!("local v = {target} -- match target"));
                 self.line(format!("local match_result{temp} -- match result"));
 
-                for (i, arm) in arms.iter().enumerate() {
-                    let check = codegen_pattern_check(&arm.pattern, "v");
+                // a guard can reject an arm after its bindings are assigned,
+                // so each arm breaks out of the loop once it has matched
+                self.line("repeat");
+                self.indent();
 
-                    let r#if = if i == 0 { "if" } else { "elseif" };
-                    self.line(format!("{if} {check} then -- match arm"));
+                for arm in arms {
+                    let check = codegen_pattern_check(&arm.pattern, "v");
+                    self.line(format!("if {check} then -- match arm"));
 
                     self.indent();
                     self.pattern_assign(&arm.pattern, "v");
 
+                    if let Some(ref guard) = arm.guard {
+                        let guard = self.expr(guard);
+                        self.line(format!("if {guard} then -- match guard"));
+                        self.indent();
+                    }
+
                     let expr = self.expr(&arm.expr);
                     self.line(format!("match_result{temp} = {expr}"));
+                    self.line("break");
+
+                    if arm.guard.is_some() {
+                        self.dedent();
+                        self.line("end");
+                    }
+
                     self.dedent();
+                    self.line("end");
                 }
 
-                self.line("end");
+                self.dedent();
+                self.line("until true");
 
                 format!("match_result{temp}")
             }
@@ -244,6 +331,7 @@ impl Codegen {
         match &pattern.kind {
             ir::PatternKind::Wildcard
             | ir::PatternKind::Int(_)
+            | ir::PatternKind::Range(_, _)
             | ir::PatternKind::Bool(_)
             | ir::PatternKind::String(_)
             | ir::PatternKind::ListEmpty
@@ -273,14 +361,84 @@ impl Codegen {
                 self.pattern_assign(pattern, &value);
             }
 
+            ir::PatternKind::Prefix(prefix, rest) => {
+                let value = format!("({value}):sub(#\"{prefix}\" + 1)");
+                self.pattern_assign(rest, &value);
+            }
+
             ir::PatternKind::ListCons(head, tail) => {
                 self.pattern_assign(head, &format!("({value})[1]"));
                 self.pattern_assign(tail, &format!("({value})[2]"));
             }
+
+            ir::PatternKind::Or(patterns) if binds(pattern) => {
+                // assign the bindings of the first alternative that matches
+                for (i, pattern) in patterns.iter().enumerate() {
+                    let check = codegen_pattern_check(pattern, value);
+
+                    let r#if = if i == 0 { "if" } else { "elseif" };
+                    self.line(format!("{if} {check} then -- or pattern assign"));
+
+                    self.indent();
+                    self.pattern_assign(pattern, value);
+                    self.dedent();
+                }
+
+                self.line("end");
+            }
+
+            ir::PatternKind::Or(_) => {}
+
+            ir::PatternKind::Record(_, fields) => {
+                let temp = self.temp;
+                self.temp += 1;
+                self.line(format!("local t{temp} = {value} -- record pattern assign"));
+
+                for (name, pattern) in fields {
+                    let field_value = format!("t{temp}[\"{name}\"]");
+                    self.pattern_assign(pattern, &field_value);
+                }
+            }
+
+            ir::PatternKind::As(pattern, lid) => {
+                self.line(format!(
+                    "l{} = {} -- pattern binding assign",
+                    lid.index(),
+                    value,
+                ));
+
+                self.pattern_assign(pattern, value);
+            }
         }
     }
 }
 
+/// Whether `pattern` binds any locals.
+fn binds(pattern: &ir::Pattern) -> bool {
+    match &pattern.kind {
+        ir::PatternKind::Binding(_) | ir::PatternKind::As(_, _) => true,
+
+        ir::PatternKind::Tuple(patterns) | ir::PatternKind::Or(patterns) => {
+            patterns.iter().any(binds)
+        }
+
+        ir::PatternKind::Record(_, fields) => fields.iter().any(|(_, p)| binds(p)),
+
+        ir::PatternKind::Variant(_, _, Some(pattern)) | ir::PatternKind::Prefix(_, pattern) => {
+            binds(pattern)
+        }
+        ir::PatternKind::ListCons(head, tail) => binds(head) || binds(tail),
+
+        ir::PatternKind::Wildcard
+        | ir::PatternKind::Int(_)
+        | ir::PatternKind::Range(_, _)
+        | ir::PatternKind::Bool(_)
+        | ir::PatternKind::String(_)
+        | ir::PatternKind::ListEmpty
+        | ir::PatternKind::Variant(_, _, None) => false,
+    }
+}
+
 fn codegen_pattern_check(pattern: &ir::Pattern, value: &str) -> String {
     match &pattern.kind {
         ir::PatternKind::Wildcard => String::from("true"),
@@ -302,6 +460,20 @@ fn codegen_pattern_check(pattern: &ir::Pattern, value: &str) -> String {
         ir::PatternKind::Bool(boolean) => format!("({boolean} == {value})"),
         ir::PatternKind::String(string) => format!("(\"{string}\" == {value})"),
 
+        ir::PatternKind::Range(lower, upper) => {
+            format!("({lower} <= {value} and {value} <= {upper})")
+        }
+
+        ir::PatternKind::Prefix(prefix, rest) => {
+            let check = format!("(({value}):sub(1, #\"{prefix}\") == \"{prefix}\")");
+            let rest_value = format!("({value}):sub(#\"{prefix}\" + 1)");
+
+            match codegen_pattern_check(rest, &rest_value).as_str() {
+                "true" => check,
+                rest_check => format!("{check} and {rest_check}"),
+            }
+        }
+
         ir::PatternKind::Variant(_, variant, pattern) => match pattern {
             Some(p) => {
                 let check = codegen_pattern_check(p, &format!("{value}.value"));
@@ -318,5 +490,50 @@ fn codegen_pattern_check(pattern: &ir::Pattern, value: &str) -> String {
 
             format!("#{value} > 0 and {head_check} and {tail_check}")
         }
+
+        ir::PatternKind::Record(_, fields) => {
+            let mut checks = Vec::new();
+
+            for (name, pattern) in fields {
+                let field_value = format!("{value}[\"{name}\"]");
+                checks.push(codegen_pattern_check(pattern, &field_value));
+            }
+
+            match checks.is_empty() {
+                true => String::from("true"),
+                false => checks.join(" and "),
+            }
+        }
+
+        ir::PatternKind::Or(patterns) => {
+            let checks = patterns
+                .iter()
+                .map(|pattern| codegen_pattern_check(pattern, value))
+                .collect::<Vec<_>>();
+
+            format!("({})", checks.join(" or "))
+        }
+
+        ir::PatternKind::As(pattern, _) => codegen_pattern_check(pattern, value),
     }
 }
+
+/// Quote `value` as a Lua string literal.
+fn quote(value: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b'\\' => {
+                quoted.push('\\');
+                quoted.push(byte as char);
+            }
+
+            b' '..=b'~' => quoted.push(byte as char),
+            _ => quoted.push_str(&format!("\\{byte:03}")),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
@@ -48,6 +48,12 @@ local function fromList(value)
 end
 
 local function equal(a, b)
+  local methods = getmetatable(a)
+
+  if methods and methods.eq then
+    return methods.eq(a)(b)
+  end
+
   if type(a) == "table" and type(b) == "table" then
     for k, v in pairs(a) do
       if not equal(v, b[k]) then
@@ -61,7 +67,37 @@ local function equal(a, b)
   end
 end
 
+local function panic(message, location)
+  io.write("thread main panic at " .. location .. ": `" .. message .. "`\n")
+  io.flush()
+  os.exit(1)
+end
+
+-- ints are 64 bit and wrap on overflow, as lua integers do, but division
+-- rounds towards negative infinity and panics on a zero divisor
+local function div(a, b, location)
+  if b == 0 then
+    panic("attempt to divide by zero", location)
+  end
+
+  return a // b
+end
+
+local function mod(a, b, location)
+  if b == 0 then
+    panic("attempt to calculate the remainder with a divisor of zero", location)
+  end
+
+  return a % b
+end
+
 local function toString(value, no_quote_strings)
+  local methods = getmetatable(value)
+
+  if methods and methods.format then
+    return methods.format(value)
+  end
+
   if isList(value) then
     local result = "["
 
@@ -100,19 +136,17 @@ local function toString(value, no_quote_strings)
   elseif isChannel(value) then
     return "channel"
   elseif isMap(value) then
-    local result = "{ "
-
-    for k, v in pairs(value) do
-      if type(v) == "table" then
-        result = result .. toString(v[1]) .. ": " .. toString(v[2])
+    local entries = {}
 
-        if next(value, k) then
-          result = result .. "; "
+    for _, bucket in pairs(value) do
+      if type(bucket) == "table" then
+        for _, v in ipairs(bucket) do
+          table.insert(entries, toString(v[1]) .. ": " .. toString(v[2]))
         end
       end
     end
 
-    return result .. " }"
+    return "{ " .. table.concat(entries, "; ") .. " }"
   elseif type(value) == "table" then
     if value.file ~= nil and value.start ~= nil and value["end"] ~= nil then
       return string.format("%s:%d..%d", value.file.path, value.start, value["end"])
@@ -153,10 +187,16 @@ local function copy(value)
     new_value[k] = v
   end
 
-  return new_value
+  return setmetatable(new_value, getmetatable(value))
 end
 
 local function toKey(value)
+  local methods = getmetatable(value)
+
+  if methods and methods.hash then
+    return "#" .. methods.hash(value)
+  end
+
   if type(value) == "table" then
     local key = ""
 
@@ -172,6 +212,34 @@ end
 
 local E = {}
 
+-- the built-in traits, for types without an impl
+E["std::eq"] = function()
+  return function(a)
+    return function(b)
+      return equal(a, b)
+    end
+  end
+end
+
+E["std::hash"] = function()
+  return function(value)
+    local key = toKey(value)
+    local hash = 5381
+
+    for i = 1, #key do
+      hash = hash * 33 + key:byte(i)
+    end
+
+    return hash
+  end
+end
+
+E["std::format"] = function()
+  return function(value)
+    return toString(value, true)
+  end
+end
+
 E["std::debug::format"] = function()
   return function(value)
     return toString(value)
@@ -454,13 +522,33 @@ E["std::map"] = function()
   return { __map = true, __len = 0 }
 end
 
+-- keys with the same `toKey` share a bucket, and are told apart with `equal`
+local function findEntry(bucket, key)
+  for i, entry in ipairs(bucket) do
+    if equal(entry[1], key) then
+      return i
+    end
+  end
+end
+
 E["std::map::put"] = function()
   return function(key)
     return function(value)
       return function(map)
+        local k = toKey(key)
+        local bucket = copy(map[k] or {})
+        local index = findEntry(bucket, key)
+
         map = copy(map)
-        map[toKey(key)] = { key, value }
-        map.__len = map.__len + 1
+
+        if index == nil then
+          table.insert(bucket, { key, value })
+          map.__len = map.__len + 1
+        else
+          bucket[index] = { key, value }
+        end
+
+        map[k] = bucket
         return map
       end
     end
@@ -470,12 +558,13 @@ end
 E["std::map::get"] = function()
   return function(key)
     return function(map)
-      local value = map[toKey(key)]
+      local bucket = map[toKey(key)] or {}
+      local index = findEntry(bucket, key)
 
-      if value == nil then
+      if index == nil then
         return { tag = "none" }
       else
-        return { tag = "some", value = value[2] }
+        return { tag = "some", value = bucket[index][2] }
       end
     end
   end
@@ -484,9 +573,25 @@ end
 E["std::map::remove"] = function()
   return function(key)
     return function(map)
+      local k = toKey(key)
+      local bucket = copy(map[k] or {})
+      local index = findEntry(bucket, key)
+
+      if index == nil then
+        return map
+      end
+
+      table.remove(bucket, index)
+
       map = copy(map)
-      map[toKey(key)] = nil
       map.__len = map.__len - 1
+
+      if #bucket == 0 then
+        map[k] = nil
+      else
+        map[k] = bucket
+      end
+
       return map
     end
   end
@@ -502,13 +607,15 @@ E["std::map::list"] = function()
   return function(map)
     local list = { __list = true }
 
-    for _, v in pairs(map) do
-      if type(v) == "table" then
-        list = {
-          __list = true,
-          { __tuple = true, v[1], v[2] },
-          list,
-        }
+    for _, bucket in pairs(map) do
+      if type(bucket) == "table" then
+        for _, v in ipairs(bucket) do
+          list = {
+            __list = true,
+            { __tuple = true, v[1], v[2] },
+            list,
+          }
+        end
       end
     end
 
@@ -517,3 +624,6 @@ E["std::map::list"] = function()
 end
 
 local M = {}
+
+-- the methods of built-in traits, by newtype, set as the metatable of its values
+local T = {}
@@ -7,11 +7,13 @@ use std::{
     process::{self, Command, Stdio},
 };
 
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use ike::{
     ast,
     diagnostic::{self, Emitter},
-    ir, lower, lsp, lua, parse, specialize,
+    ir,
+    lint::{LintLevel, Lints},
+    lower, lsp, lua, parse, specialize,
 };
 
 #[derive(Parser)]
@@ -25,12 +27,84 @@ enum Commands {
     /// Run the language server.
     Lsp,
 
-    Run(RunArgs),
+    /// Build and run a package.
+    Run(BuildArgs),
+
+    /// Build a package without running it.
+    Build(BuildArgs),
+
+    /// Check a package for errors without generating code.
+    Check(BuildArgs),
+
+    /// Print a detailed explanation of an error code.
+    Explain(ExplainArgs),
+}
+
+#[derive(Parser)]
+struct BuildArgs {
+    /// The package to build, either a file or a directory.
+    package: PathBuf,
+
+    /// The format to emit diagnostics in.
+    #[arg(long, value_enum, default_value_t)]
+    message_format: MessageFormat,
+
+    /// Set a lint to warn, `warnings` sets every lint.
+    #[arg(short = 'W', long = "warn", value_name = "LINT")]
+    warn: Vec<String>,
+
+    /// Set a lint to deny, making it an error.
+    #[arg(short = 'D', long = "deny", value_name = "LINT")]
+    deny: Vec<String>,
+
+    /// Set a lint to allow, silencing it.
+    #[arg(short = 'A', long = "allow", value_name = "LINT")]
+    allow: Vec<String>,
+}
+
+impl BuildArgs {
+    /// The lint levels of the flags, applied in the order they were given,
+    /// so that later flags override earlier ones.
+    fn lints(&self, matches: &ArgMatches) -> Result<Lints, Box<dyn Error>> {
+        let mut lints = Lints::new();
+
+        let levels = [
+            ("allow", &self.allow, LintLevel::Allow),
+            ("warn", &self.warn, LintLevel::Warn),
+            ("deny", &self.deny, LintLevel::Deny),
+        ];
+
+        let mut flags = Vec::new();
+
+        for (id, names, level) in levels {
+            let indices = matches.indices_of(id).into_iter().flatten();
+            flags.extend(indices.zip(names).map(|(index, name)| (index, name, level)));
+        }
+
+        flags.sort_by_key(|(index, _, _)| *index);
+
+        for (_, name, level) in flags {
+            lints.set(name, level)?;
+        }
+
+        Ok(lints)
+    }
 }
 
 #[derive(Parser)]
-struct RunArgs {
-    package: Option<PathBuf>,
+struct ExplainArgs {
+    /// The error code to explain, e.g. `E0001`.
+    code: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum MessageFormat {
+    /// Colored, human readable text written to stderr.
+    #[default]
+    Human,
+
+    /// One JSON object per diagnostic written to stdout.
+    Json,
 }
 
 #[derive(Debug)]
@@ -89,47 +163,19 @@ enum PackageKind {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches)?;
+
+    // the order of lint flags is only known from the matches
+    let matches = match matches.subcommand() {
+        Some((_, matches)) => matches.clone(),
+        None => matches,
+    };
 
     match args.command {
         Commands::Lsp => lsp::LanguageServer::new()?.run(),
         Commands::Run(args) => {
-            let target_path = args.package.unwrap();
-            let target_name = target_path
-                .file_stem()
-                .unwrap()
-                .to_string_lossy()
-                .to_string();
-
-            let options = BuildOptions {
-                packages: vec![
-                    Package {
-                        path: PathBuf::from("std"),
-                        name: String::from("std"),
-                        kind: PackageKind::Library,
-                    },
-                    Package {
-                        path: target_path,
-                        name: target_name,
-                        kind: PackageKind::Binary,
-                    },
-                ],
-            };
-
-            options.verify()?;
-
-            let mut sources = diagnostic::Sources::new();
-            let mut emitter = Vec::new();
-
-            if let Err(err) = compile(&mut sources, &mut emitter, &options) {
-                for diagnostic in emitter {
-                    diagnostic.print(&sources);
-                }
-
-                println!("{err}");
-
-                process::exit(1);
-            }
+            build(&args, &matches, Stage::Codegen)?;
 
             Command::new("lua")
                 .arg("test.lua")
@@ -139,18 +185,83 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .stderr(Stdio::inherit())
                 .output()?;
 
+            Ok(())
+        }
+        Commands::Build(args) => build(&args, &matches, Stage::Codegen),
+        Commands::Check(args) => build(&args, &matches, Stage::Check),
+        Commands::Explain(args) => {
+            let code = diagnostic::Code::lookup(&args.code)
+                .ok_or_else(|| format!("`{}` is not a valid error code", args.code))?;
+
+            println!("{}", code.explanation());
+
             Ok(())
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    Check,
+    Codegen,
+}
+
+fn build(args: &BuildArgs, matches: &ArgMatches, stage: Stage) -> Result<(), Box<dyn Error>> {
+    let target_path = args.package.clone();
+    let target_name = target_path
+        .file_stem()
+        .ok_or_else(|| format!("`{}` is not a package", target_path.display()))?
+        .to_string_lossy()
+        .to_string();
+
+    let options = BuildOptions {
+        packages: vec![
+            Package {
+                path: PathBuf::from("std"),
+                name: String::from("std"),
+                kind: PackageKind::Library,
+            },
+            Package {
+                path: target_path,
+                name: target_name,
+                kind: PackageKind::Binary,
+            },
+        ],
+    };
+
+    options.verify()?;
+
+    let lints = args.lints(matches)?;
+
+    let mut sources = diagnostic::Sources::new();
+    let mut emitter = Vec::new();
+
+    let result = compile(&mut sources, &mut emitter, &options, lints, stage);
+
+    for diagnostic in &emitter {
+        match args.message_format {
+            MessageFormat::Human => diagnostic.print(&sources),
+            MessageFormat::Json => println!("{}", diagnostic.to_json(&sources)),
+        }
+    }
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
 #[allow(unused)]
 fn lower(
     sources: &mut diagnostic::Sources,
     emitter: &mut dyn Emitter,
     options: &BuildOptions,
+    lints: Lints,
 ) -> Result<ir::untyped::Program, Box<dyn Error>> {
-    let mut lowerer = lower::Lowerer::new(emitter);
+    let mut lowerer = lower::Lowerer::new(emitter).with_lints(lints);
 
     for package in &options.packages {
         let module = match package.path.is_dir() {
@@ -168,15 +279,20 @@ fn lower(
         lowerer.add_module(&[&package.name], &module)?;
     }
 
-    lowerer.finish().map_err(From::from)
+    let (program, result) = lowerer.finish();
+    result?;
+
+    Ok(program)
 }
 
 fn compile(
     sources: &mut diagnostic::Sources,
-    emitter: &mut dyn Emitter,
+    emitter: &mut Vec<diagnostic::Diagnostic>,
     options: &BuildOptions,
+    mut lints: Lints,
+    stage: Stage,
 ) -> Result<(), Box<dyn Error>> {
-    let ir = lower(sources, emitter, options)?;
+    let ir = lower(sources, emitter, options, lints.clone())?;
 
     let binary = options.binary().unwrap();
 
@@ -192,10 +308,24 @@ fn compile(
             ))
         })?;
 
+    lower::lint_unreachable(&ir, &binary.name, entry, &mut lints, emitter);
+
+    // denied lints are reported as errors, but do not stop lowering
+    if emitter.iter().any(|d| d.level == diagnostic::Level::Error) {
+        return Err(From::from(format!(
+            "could not compile `{}` due to previous errors",
+            binary.name
+        )));
+    }
+
     let (ir, entry) = specialize::specialize(ir, entry, emitter)?;
 
+    if stage == Stage::Check {
+        return Ok(());
+    }
+
     let mut file = File::create("test.lua")?;
-    lua::codegen(&mut file, &ir, entry)?;
+    lua::codegen(&mut file, &ir, sources, entry)?;
 
     Ok(())
 }
@@ -1,23 +1,27 @@
 use std::mem;
 
 use crate::{
-    ast::{Arm, BinOp, Expr, ExprKind},
-    diagnostic::Diagnostic,
+    ast::{Arm, BinOp, Expr, ExprKind, UnOp},
+    diagnostic::{Diagnostic, code},
 };
 
 use super::{
     Token, TokenStream, consume_newlines, parse_ident, parse_irrefutable_pattern, parse_path,
-    parse_pattern, tokenize,
+    parse_pattern, parse_pattern_ascription, parse_type, tokenize,
 };
 
 #[derive(Clone, Copy)]
 struct Options {
     allow_block: bool,
+    allow_lambda: bool,
 }
 
 impl Default for Options {
     fn default() -> Self {
-        Self { allow_block: true }
+        Self {
+            allow_block: true,
+            allow_lambda: false,
+        }
     }
 }
 
@@ -26,6 +30,11 @@ impl Options {
         self.allow_block = allow;
         self
     }
+
+    fn allow_lambda(mut self, allow: bool) -> Self {
+        self.allow_lambda = allow;
+        self
+    }
 }
 
 fn parse_bool_expr(tokens: &mut TokenStream) -> Result<Expr, Diagnostic> {
@@ -35,8 +44,20 @@ fn parse_bool_expr(tokens: &mut TokenStream) -> Result<Expr, Diagnostic> {
 }
 
 fn parse_paren_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Diagnostic> {
-    tokens.expect(&Token::LParen)?;
+    let start = tokens.expect(&Token::LParen)?;
     let expr = parse_expr_impl(tokens, options.allow_block(true))?;
+
+    if tokens.is(&Token::Colon) {
+        tokens.consume();
+
+        let ty = parse_type(tokens)?;
+        let end = tokens.expect(&Token::RParen)?;
+
+        let span = start.join(end);
+        let kind = ExprKind::Ascription(Box::new(expr), ty);
+        return Ok(kind.with_span(span));
+    }
+
     tokens.expect(&Token::RParen)?;
 
     Ok(expr)
@@ -101,14 +122,20 @@ pub fn parse_block_expr(tokens: &mut TokenStream) -> Result<Expr, Diagnostic> {
     Ok(kind.with_span(span))
 }
 
+/// Parse a lambda, e.g. `|x y| x + y`.
+///
+/// A `|` following an argument is bitwise or, so a lambda passed as an
+/// argument is wrapped in parentheses, `f (|x| x)`, except in a stage of a
+/// pipeline, `xs |> list::map |x| x`, where bitwise or is wrapped instead.
 fn parse_lambda_expr(tokens: &mut TokenStream) -> Result<Expr, Diagnostic> {
     let start = tokens.expect(&Token::Pipe)?;
 
     let mut params = Vec::new();
 
     while !tokens.is(&Token::Pipe) {
-        let parma = parse_irrefutable_pattern(tokens)?;
-        params.push(parma);
+        let param = parse_irrefutable_pattern(tokens)?;
+        let param = parse_pattern_ascription(tokens, param)?;
+        params.push(param);
     }
 
     tokens.expect(&Token::Pipe)?;
@@ -120,8 +147,8 @@ fn parse_lambda_expr(tokens: &mut TokenStream) -> Result<Expr, Diagnostic> {
     Ok(kind.with_span(span))
 }
 
-fn is_term_expr(token: &TokenStream, options: Options) -> bool {
-    let (token, _) = token.peek();
+fn is_term_expr(tokens: &TokenStream, options: Options) -> bool {
+    let (token, _) = tokens.peek();
 
     match token {
         Token::Ident(_)
@@ -131,7 +158,11 @@ fn is_term_expr(token: &TokenStream, options: Options) -> bool {
         | Token::False
         | Token::LParen
         | Token::LBracket
-        | Token::Pipe => true,
+        | Token::Under
+        | Token::Question => true,
+
+        // see `parse_lambda_expr`
+        Token::Pipe => options.allow_lambda,
 
         Token::LBrace if options.allow_block => true,
 
@@ -209,6 +240,7 @@ fn parse_term_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, D
 
                 let Some(end) = rest[idx..].find("}") else {
                     let diagnostic = Diagnostic::error("no end of expression in format string")
+                        .with_code(code::E0003)
                         .with_label(span, "in string here");
 
                     return Err(diagnostic);
@@ -252,8 +284,24 @@ fn parse_term_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, D
         Token::LBracket => parse_list_expr(tokens),
         Token::Pipe => parse_lambda_expr(tokens),
 
+        Token::Under => {
+            tokens.consume();
+            Ok(ExprKind::Hole(None).with_span(span))
+        }
+
+        Token::Question => {
+            tokens.consume();
+
+            let (name, name_span) = parse_ident(tokens)?;
+
+            let span = span.join(name_span);
+            Ok(ExprKind::Hole(Some(name)).with_span(span))
+        }
+
         _ => {
-            let diagnostic = Diagnostic::error("unexpected token in expression").with_span(span);
+            let diagnostic = Diagnostic::error("unexpected token in expression")
+                .with_code(code::E0002)
+                .with_span(span);
             Err(diagnostic)
         }
     }
@@ -338,15 +386,34 @@ fn parse_try_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Di
     parse_call_expr(tokens, options)
 }
 
+fn parse_unary_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Diagnostic> {
+    let (token, op_span) = tokens.peek();
+
+    let op = match token {
+        Token::Minus => UnOp::Neg,
+        Token::Bang | Token::Not => UnOp::Not,
+        Token::Tilde => UnOp::BitNot,
+        _ => return parse_try_expr(tokens, options),
+    };
+
+    tokens.consume();
+
+    let expr = parse_unary_expr(tokens, options)?;
+
+    let span = op_span.join(expr.span);
+    let kind = ExprKind::Unary(op, op_span, Box::new(expr));
+    Ok(kind.with_span(span))
+}
+
 fn parse_mul_div_mod_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Diagnostic> {
-    let mut lhs = parse_try_expr(tokens, options)?;
+    let mut lhs = parse_unary_expr(tokens, options)?;
 
     let (mut token, op_span) = tokens.peek();
 
     while matches!(token, Token::Star | Token::Slash | Token::Percent) {
         tokens.consume();
 
-        let rhs = parse_try_expr(tokens, options)?;
+        let rhs = parse_unary_expr(tokens, options)?;
 
         let op = match token {
             Token::Star => BinOp::Mul,
@@ -417,15 +484,63 @@ fn parse_shl_shr_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr
     Ok(lhs)
 }
 
-fn parse_cmp_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Diagnostic> {
+fn parse_bit_and_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Diagnostic> {
     let mut lhs = parse_shl_shr_expr(tokens, options)?;
 
+    while tokens.is(&Token::Amp) {
+        let op_span = tokens.consume();
+
+        let rhs = parse_shl_shr_expr(tokens, options)?;
+
+        let span = lhs.span.join(rhs.span);
+        let kind = ExprKind::Binary(BinOp::BitAnd, op_span, Box::new(lhs), Box::new(rhs));
+        lhs = kind.with_span(span);
+    }
+
+    Ok(lhs)
+}
+
+fn parse_bit_xor_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Diagnostic> {
+    let mut lhs = parse_bit_and_expr(tokens, options)?;
+
+    while tokens.is(&Token::Caret) {
+        let op_span = tokens.consume();
+
+        let rhs = parse_bit_and_expr(tokens, options)?;
+
+        let span = lhs.span.join(rhs.span);
+        let kind = ExprKind::Binary(BinOp::BitXor, op_span, Box::new(lhs), Box::new(rhs));
+        lhs = kind.with_span(span);
+    }
+
+    Ok(lhs)
+}
+
+fn parse_bit_or_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Diagnostic> {
+    let mut lhs = parse_bit_xor_expr(tokens, options)?;
+
+    while tokens.is(&Token::Pipe) {
+        let op_span = tokens.consume();
+
+        let rhs = parse_bit_xor_expr(tokens, options)?;
+
+        let span = lhs.span.join(rhs.span);
+        let kind = ExprKind::Binary(BinOp::BitOr, op_span, Box::new(lhs), Box::new(rhs));
+        lhs = kind.with_span(span);
+    }
+
+    Ok(lhs)
+}
+
+fn parse_cmp_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Diagnostic> {
+    let mut lhs = parse_bit_or_expr(tokens, options)?;
+
     let (mut token, op_span) = tokens.peek();
 
     while matches!(token, Token::Gt | Token::Lt | Token::GtEq | Token::LtEq) {
         tokens.consume();
 
-        let rhs = parse_shl_shr_expr(tokens, options)?;
+        let rhs = parse_bit_or_expr(tokens, options)?;
 
         let op = match token {
             Token::Gt => BinOp::Gt,
@@ -568,7 +683,7 @@ fn parse_pipe_right_expr(tokens: &mut TokenStream, options: Options) -> Result<E
         consume_newlines(tokens);
         tokens.expect(&Token::PipeGt)?;
 
-        let callee = parse_pipe_left_expr(tokens, options)?;
+        let callee = parse_pipe_left_expr(tokens, options.allow_lambda(true))?;
 
         let span = expr.span.join(callee.span);
         let kind = ExprKind::Call(Box::new(callee), Box::new(expr));
@@ -582,6 +697,7 @@ fn parse_let_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr, Di
     let span = tokens.expect(&Token::Let)?;
 
     let pattern = parse_pattern(tokens)?;
+    let pattern = parse_pattern_ascription(tokens, pattern)?;
 
     tokens.expect(&Token::Eq)?;
 
@@ -606,6 +722,13 @@ fn parse_match_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr,
     while !tokens.is(&Token::RBrace) {
         let pattern = parse_pattern(tokens)?;
 
+        let guard = if tokens.is(&Token::If) {
+            tokens.consume();
+            Some(parse_expr_impl(tokens, options)?)
+        } else {
+            None
+        };
+
         tokens.expect(&Token::RArrow)?;
 
         let expr = parse_expr_impl(tokens, options)?;
@@ -613,6 +736,7 @@ fn parse_match_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr,
         let span = pattern.span.join(expr.span);
         let arm = Arm {
             pattern,
+            guard,
             expr,
             span,
         };
@@ -628,12 +752,46 @@ fn parse_match_expr(tokens: &mut TokenStream, options: Options) -> Result<Expr,
     Ok(kind.with_span(span))
 }
 
+fn parse_if_expr(tokens: &mut TokenStream) -> Result<Expr, Diagnostic> {
+    let start = tokens.expect(&Token::If)?;
+
+    let condition = parse_expr_impl(tokens, Options::default().allow_block(false))?;
+    let then = parse_block_expr(tokens)?;
+
+    // `else` may start the line after the `}`
+    let mut n = 0;
+
+    while tokens.nth_is(n, &Token::Newline) {
+        n += 1;
+    }
+
+    if !tokens.nth_is(n, &Token::Else) {
+        let span = start.join(then.span);
+        let kind = ExprKind::If(Box::new(condition), Box::new(then), None);
+        return Ok(kind.with_span(span));
+    }
+
+    consume_newlines(tokens);
+    tokens.expect(&Token::Else)?;
+
+    let r#else = match tokens.is(&Token::If) {
+        true => parse_if_expr(tokens)?,
+        false => parse_block_expr(tokens)?,
+    };
+
+    let span = start.join(r#else.span);
+    let kind = ExprKind::If(Box::new(condition), Box::new(then), Some(Box::new(r#else)));
+    Ok(kind.with_span(span))
+}
+
 fn parse_expr_impl(tokens: &mut TokenStream, options: Options) -> Result<Expr, Diagnostic> {
+    let options = options.allow_lambda(false);
     let (token, _) = tokens.peek();
 
     match token {
         Token::Let => parse_let_expr(tokens, options),
         Token::Match => parse_match_expr(tokens, options),
+        Token::If => parse_if_expr(tokens),
         _ => parse_pipe_right_expr(tokens, options),
     }
 }
@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use crate::diagnostic::{Diagnostic, Emitter, Sid, Span};
+use crate::diagnostic::{Diagnostic, Emitter, Sid, Span, code};
 
 use super::{Token, TokenStream};
 
@@ -166,7 +166,27 @@ pub fn tokenize(
             continue;
         }
 
-        if c != 'f' && c != 'o' {
+        // `_` on its own is a wildcard, but `_name` is an identifier
+        if c == '_' && lexer.remaining()[1..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        {
+            let token = lex_ident(&mut lexer);
+            tokens.push((token, lexer.span_from(start)));
+            continue;
+        }
+
+        if c != 'a' && c != 'f' && c != 'i' && c != 'o' {
+            // handle three-character symbols
+            if !is_ident_start(c)
+                && let Some(symbol) = lexer.remaining().get(..3)
+                && let Ok(token) = Token::from_str(symbol)
+            {
+                lexer.advance();
+                lexer.advance();
+                lexer.advance();
+                tokens.push((token, lexer.span_from(start)));
+                continue;
+            }
+
             // handle two-character symbols
             if lexer.remaining().len() >= 2
                 && let Ok(token) = Token::from_str(&lexer.remaining()[..2])
@@ -193,7 +213,9 @@ pub fn tokenize(
 
         let span = lexer.span_from(start);
         let message = format!("Unexpected character: '{c}'");
-        let diagnostic = Diagnostic::error(message).with_span(span);
+        let diagnostic = Diagnostic::error(message)
+            .with_code(code::E0001)
+            .with_span(span);
         emitter.emit(diagnostic);
 
         is_error = true;
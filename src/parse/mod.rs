@@ -14,7 +14,7 @@ pub use stream::*;
 pub use token::*;
 pub use r#type::*;
 
-use crate::diagnostic::{Diagnostic, Span};
+use crate::diagnostic::{Diagnostic, Span, code};
 
 fn parse_ident(tokens: &mut TokenStream) -> Result<(String, Span), Diagnostic> {
     let (token, span) = tokens.peek();
@@ -26,7 +26,9 @@ fn parse_ident(tokens: &mut TokenStream) -> Result<(String, Span), Diagnostic> {
         }
 
         _ => {
-            let diagnostic = Diagnostic::error("expected identifier").with_span(span);
+            let diagnostic = Diagnostic::error("expected identifier")
+                .with_code(code::E0002)
+                .with_span(span);
             Err(diagnostic)
         }
     }
@@ -1,14 +1,15 @@
 use crate::{
     ast::{
-        Ascription, Extern, Field, File, Function, Import, Item, Newtype, NewtypeKind, Path,
-        Variant,
+        Ascription, Extern, Field, File, Function, Impl, Import, Item, Newtype, NewtypeKind, Path,
+        Trait, Variant,
     },
-    diagnostic::{Diagnostic, Emitter},
+    attr::{Attr, Attrs},
+    diagnostic::{Diagnostic, Emitter, code},
 };
 
 use super::{
     Token, TokenStream, consume_newlines, parse_block_expr, parse_expr, parse_ident,
-    parse_irrefutable_pattern, parse_type,
+    parse_irrefutable_pattern, parse_term_type, parse_type,
 };
 
 pub fn parse_path(tokens: &mut TokenStream) -> Result<Path, Diagnostic> {
@@ -32,7 +33,11 @@ fn parse_import(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
     let span = tokens.expect("import")?;
 
     let path = parse_path(tokens)?;
-    let import = Import { path, span };
+    let import = Import {
+        path,
+        span,
+        attrs: Attrs::default(),
+    };
 
     Ok(Item::Import(import))
 }
@@ -116,6 +121,7 @@ fn parse_newtype(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
                 generics,
                 kind,
                 span,
+                attrs: Attrs::default(),
             };
 
             Ok(Item::Newtype(newtype))
@@ -140,6 +146,7 @@ fn parse_newtype(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
                 generics,
                 kind,
                 span,
+                attrs: Attrs::default(),
             };
 
             Ok(Item::Newtype(newtype))
@@ -171,6 +178,7 @@ fn parse_alias(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
         generics,
         kind,
         span,
+        attrs: Attrs::default(),
     };
 
     Ok(Item::Newtype(newtype))
@@ -186,7 +194,12 @@ fn parse_function(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
 
         let ty = parse_type(tokens)?;
 
-        let ascription = Ascription { name, ty, span };
+        let ascription = Ascription {
+            name,
+            ty,
+            span,
+            attrs: Attrs::default(),
+        };
         return Ok(Item::Ascription(ascription));
     }
 
@@ -215,6 +228,7 @@ fn parse_function(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
         params,
         body,
         span,
+        attrs: Attrs::default(),
     };
 
     Ok(Item::Function(function))
@@ -229,11 +243,143 @@ fn parse_extern(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
 
     let ty = parse_type(tokens)?;
 
-    let r#extern = Extern { name, ty, span };
+    let r#extern = Extern {
+        name,
+        ty,
+        span,
+        attrs: Attrs::default(),
+    };
 
     Ok(Item::Extern(r#extern))
 }
 
+fn parse_trait(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
+    let span = tokens.expect("trait")?;
+
+    let name = parse_path(tokens)?;
+
+    let quote_span = tokens.expect(&Token::Quote)?;
+    let (generic, generic_span) = parse_ident(tokens)?;
+
+    tokens.expect(&Token::LBrace)?;
+    consume_newlines(tokens);
+
+    let mut methods = Vec::new();
+
+    while !tokens.is(&Token::RBrace) {
+        let (_, method_span) = tokens.peek();
+
+        let Item::Ascription(method) = parse_function(tokens)? else {
+            let diagnostic = Diagnostic::error("expected method signature")
+                .with_code(code::E0002)
+                .with_span(method_span);
+
+            return Err(diagnostic);
+        };
+
+        methods.push(method);
+        consume_newlines(tokens);
+    }
+
+    tokens.expect(&Token::RBrace)?;
+
+    let r#trait = Trait {
+        name,
+        generic: (generic, quote_span.join(generic_span)),
+        methods,
+        span,
+        attrs: Attrs::default(),
+    };
+
+    Ok(Item::Trait(r#trait))
+}
+
+fn parse_impl(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
+    let span = tokens.expect("impl")?;
+
+    let r#trait = parse_path(tokens)?;
+
+    // the type must be a single term, so that the brace is not taken as `{}`
+    let ty = parse_term_type(tokens)?;
+
+    tokens.expect(&Token::LBrace)?;
+    consume_newlines(tokens);
+
+    let mut functions = Vec::new();
+
+    while !tokens.is(&Token::RBrace) {
+        let (_, function_span) = tokens.peek();
+
+        let Item::Function(function) = parse_function(tokens)? else {
+            let diagnostic = Diagnostic::error("expected method")
+                .with_code(code::E0002)
+                .with_span(function_span);
+
+            return Err(diagnostic);
+        };
+
+        functions.push(function);
+        consume_newlines(tokens);
+    }
+
+    tokens.expect(&Token::RBrace)?;
+
+    let r#impl = Impl {
+        r#trait,
+        ty,
+        functions,
+        span,
+        attrs: Attrs::default(),
+    };
+
+    Ok(Item::Impl(r#impl))
+}
+
+/// Parse the attributes preceding an item, e.g. `#[allow(dead-code, unused-variables)]`.
+///
+/// Each value in the parentheses becomes its own attribute.
+fn parse_attrs(tokens: &mut TokenStream) -> Result<Attrs, Diagnostic> {
+    let mut attrs = Vec::new();
+
+    while tokens.is(&Token::Pound) {
+        tokens.consume();
+        tokens.expect(&Token::LBracket)?;
+
+        let (name, span) = parse_ident(tokens)?;
+
+        if tokens.is(&Token::LParen) {
+            tokens.consume();
+
+            while !tokens.is(&Token::RParen) {
+                let (value, value_span) = parse_ident(tokens)?;
+
+                attrs.push(Attr {
+                    name: name.clone(),
+                    value,
+                    span: span.join(value_span),
+                });
+
+                if !tokens.is(&Token::RParen) {
+                    tokens.expect(&Token::Comma)?;
+                }
+            }
+
+            tokens.consume();
+        } else {
+            attrs.push(Attr {
+                name,
+                value: String::new(),
+                span,
+            });
+        }
+
+        tokens.expect(&Token::RBracket)?;
+        consume_newlines(tokens);
+    }
+
+    Ok(Attrs { attrs })
+}
+
 fn parse_item(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
     let (token, span) = tokens.peek();
 
@@ -244,14 +390,20 @@ fn parse_item(tokens: &mut TokenStream) -> Result<Item, Diagnostic> {
             "fn" => parse_function(tokens),
             "import" => parse_import(tokens),
             "extern" => parse_extern(tokens),
+            "trait" => parse_trait(tokens),
+            "impl" => parse_impl(tokens),
             _ => {
-                let diagnostic = Diagnostic::error("expected item").with_span(span);
+                let diagnostic = Diagnostic::error("expected item")
+                    .with_code(code::E0002)
+                    .with_span(span);
                 Err(diagnostic)
             }
         },
 
         _ => {
-            let diagnostic = Diagnostic::error("expected item").with_span(span);
+            let diagnostic = Diagnostic::error("expected item")
+                .with_code(code::E0002)
+                .with_span(span);
             Err(diagnostic)
         }
     }
@@ -264,7 +416,13 @@ pub fn parse_file(tokens: &mut TokenStream, emitter: &mut dyn Emitter) -> Result
     consume_newlines(tokens);
 
     while !tokens.is(&Token::Eof) {
-        match parse_item(tokens) {
+        let item = parse_attrs(tokens).and_then(|attrs| {
+            let mut item = parse_item(tokens)?;
+            *item.attrs_mut() = attrs;
+            Ok(item)
+        });
+
+        match item {
             Ok(item) => items.push(item),
             Err(err) => {
                 emitter.emit(err);
@@ -1,9 +1,27 @@
 use crate::{
-    ast::{Pattern, PatternKind},
-    diagnostic::Diagnostic,
+    ast::{Path, Pattern, PatternKind},
+    diagnostic::{Diagnostic, Span, code},
 };
 
-use super::{Token, TokenStream, parse_path};
+use super::{Token, TokenStream, consume_newlines, parse_ident, parse_path, parse_type};
+
+#[derive(Clone, Copy)]
+struct Options {
+    allow_refutable: bool,
+    allow_record: bool,
+}
+
+impl Options {
+    fn allow_refutable(mut self, allow: bool) -> Self {
+        self.allow_refutable = allow;
+        self
+    }
+
+    fn allow_record(mut self, allow: bool) -> Self {
+        self.allow_record = allow;
+        self
+    }
+}
 
 fn is_pattern(tokens: &TokenStream) -> bool {
     let (token, _) = tokens.peek();
@@ -14,16 +32,26 @@ fn is_pattern(tokens: &TokenStream) -> bool {
             | Token::True
             | Token::False
             | Token::Ident(_)
+            | Token::Integer(_)
+            | Token::Minus
             | Token::String(_)
             | Token::LParen
             | Token::LBracket
     )
 }
 
-fn parse_pattern_term(
-    tokens: &mut TokenStream,
-    allow_refutable: bool,
-) -> Result<Pattern, Diagnostic> {
+/// Whether the next token is a `|`, possibly on a following line.
+fn is_alternative(tokens: &TokenStream) -> bool {
+    let mut n = 0;
+
+    while tokens.nth_is(n, &Token::Newline) {
+        n += 1;
+    }
+
+    tokens.nth_is(n, &Token::Pipe)
+}
+
+fn parse_pattern_term(tokens: &mut TokenStream, options: Options) -> Result<Pattern, Diagnostic> {
     let (token, span) = tokens.peek();
 
     match token {
@@ -34,14 +62,14 @@ fn parse_pattern_term(
             Ok(Pattern { kind, span })
         }
 
-        Token::True if allow_refutable => {
+        Token::True if options.allow_refutable => {
             tokens.consume();
 
             let kind = PatternKind::Bool(true);
             Ok(Pattern { kind, span })
         }
 
-        Token::False if allow_refutable => {
+        Token::False if options.allow_refutable => {
             tokens.consume();
 
             let kind = PatternKind::Bool(false);
@@ -51,8 +79,12 @@ fn parse_pattern_term(
         Token::Ident(_) => {
             let path = parse_path(tokens)?;
 
-            if is_pattern(tokens) && allow_refutable {
-                let pattern = parse_pattern_term(tokens, allow_refutable)?;
+            if tokens.is(&Token::LBrace) && options.allow_record {
+                return parse_record_pattern(tokens, path, options);
+            }
+
+            if is_pattern(tokens) && options.allow_refutable {
+                let pattern = parse_pattern_term(tokens, options)?;
 
                 let span = span.join(pattern.span);
                 let kind = PatternKind::Variant(path, Box::new(pattern));
@@ -63,8 +95,18 @@ fn parse_pattern_term(
             Ok(Pattern { kind, span })
         }
 
-        Token::Integer(value) => {
-            tokens.consume();
+        Token::Integer(_) | Token::Minus => {
+            let (value, span) = parse_integer(tokens)?;
+
+            if tokens.is(&Token::DotDotEq) {
+                tokens.consume();
+
+                let (upper, end) = parse_integer(tokens)?;
+
+                let span = span.join(end);
+                let kind = PatternKind::Range(value, upper);
+                return Ok(Pattern { kind, span });
+            }
 
             let kind = PatternKind::Int(value);
             Ok(Pattern { kind, span })
@@ -74,19 +116,47 @@ fn parse_pattern_term(
             tokens.consume();
 
             let string = string.replace("{{", "{").replace("}}", "}");
+
+            if tokens.is(&Token::PlusPlus) && options.allow_refutable {
+                tokens.consume();
+
+                let rest = parse_pattern_term(tokens, options)?;
+
+                let span = span.join(rest.span);
+                let kind = PatternKind::Prefix(string, Box::new(rest));
+                return Ok(Pattern { kind, span });
+            }
+
             let kind = PatternKind::String(string);
             Ok(Pattern { kind, span })
         }
 
         Token::LParen => {
             tokens.consume();
-            let pattern = parse_pattern_impl(tokens, allow_refutable)?;
+
+            // inside parentheses a variant cannot be confused with the patterns
+            // following it, so any pattern is parsed, and whether it is
+            // refutable is left to lowering
+            let options = options.allow_refutable(true).allow_record(true);
+            let pattern = parse_pattern_impl(tokens, options)?;
+
+            if tokens.is(&Token::Colon) {
+                tokens.consume();
+
+                let ty = parse_type(tokens)?;
+                let end = tokens.expect(&Token::RParen)?;
+
+                let span = span.join(end);
+                let kind = PatternKind::Ascription(Box::new(pattern), ty);
+                return Ok(Pattern { kind, span });
+            }
+
             tokens.expect(&Token::RParen)?;
 
             Ok(pattern)
         }
 
-        Token::LBracket if allow_refutable => {
+        Token::LBracket if options.allow_refutable => {
             tokens.consume();
 
             let mut patterns = Vec::new();
@@ -101,7 +171,7 @@ fn parse_pattern_term(
                             kind: PatternKind::Wildcard,
                             span,
                         },
-                        false => parse_pattern_impl(tokens, allow_refutable)?,
+                        false => parse_pattern_impl(tokens, options)?,
                     };
 
                     rest = Some(Box::new(pattern));
@@ -109,7 +179,7 @@ fn parse_pattern_term(
                     break;
                 }
 
-                let pattern = parse_pattern_impl(tokens, allow_refutable)?;
+                let pattern = parse_pattern_impl(tokens, options)?;
                 patterns.push(pattern);
 
                 if !tokens.is(&Token::RBracket) {
@@ -125,17 +195,136 @@ fn parse_pattern_term(
         }
 
         _ => {
-            let diagnostic = Diagnostic::error("expected pattern").with_span(span);
+            let diagnostic = Diagnostic::error("expected pattern")
+                .with_code(code::E0002)
+                .with_span(span);
             Err(diagnostic)
         }
     }
 }
 
-fn parse_pattern_impl(
+/// Parse an integer, which may be negated with `-`.
+fn parse_integer(tokens: &mut TokenStream) -> Result<(i64, Span), Diagnostic> {
+    let (_, start) = tokens.peek();
+
+    let negative = tokens.is(&Token::Minus);
+
+    if negative {
+        tokens.consume();
+    }
+
+    let (token, end) = tokens.peek();
+
+    let Token::Integer(value) = token else {
+        let diagnostic = Diagnostic::error("expected integer")
+            .with_code(code::E0002)
+            .with_span(end);
+        return Err(diagnostic);
+    };
+
+    tokens.consume();
+
+    let value = if negative { -value } else { value };
+    Ok((value, start.join(end)))
+}
+
+fn parse_record_pattern(
     tokens: &mut TokenStream,
-    allow_refutable: bool,
+    path: Path,
+    options: Options,
 ) -> Result<Pattern, Diagnostic> {
-    let pattern = parse_pattern_term(tokens, allow_refutable)?;
+    tokens.expect(&Token::LBrace)?;
+
+    consume_newlines(tokens);
+
+    let mut fields = Vec::new();
+    let mut rest = false;
+
+    while !tokens.is(&Token::RBrace) {
+        if tokens.is(&Token::DotDot) {
+            tokens.consume();
+            consume_newlines(tokens);
+
+            rest = true;
+            break;
+        }
+
+        let (name, span) = parse_ident(tokens)?;
+
+        // `name` on its own binds the field to a local of the same name
+        let pattern = match tokens.is(&Token::Colon) {
+            true => {
+                tokens.consume();
+                parse_pattern_impl(tokens, options.allow_record(true))?
+            }
+
+            false => Pattern {
+                kind: PatternKind::Path(Path {
+                    segments: vec![name.clone()],
+                    span,
+                }),
+                span,
+            },
+        };
+
+        fields.push((name, pattern, span));
+
+        if !(tokens.is(&Token::Newline) || tokens.is(&Token::RBrace)) {
+            tokens.expect(&Token::Semi)?;
+        }
+
+        consume_newlines(tokens);
+    }
+
+    let end = tokens.expect(&Token::RBrace)?;
+
+    let span = path.span.join(end);
+    let kind = PatternKind::Record(path, fields, rest);
+    Ok(Pattern { kind, span })
+}
+
+fn parse_pattern_impl(tokens: &mut TokenStream, options: Options) -> Result<Pattern, Diagnostic> {
+    let mut pattern = parse_or_pattern(tokens, options)?;
+
+    while tokens.is(&Token::As) {
+        tokens.consume();
+
+        let (name, name_span) = parse_ident(tokens)?;
+
+        let span = pattern.span.join(name_span);
+        let kind = PatternKind::As(Box::new(pattern), name, name_span);
+        pattern = Pattern { kind, span };
+    }
+
+    Ok(pattern)
+}
+
+fn parse_or_pattern(tokens: &mut TokenStream, options: Options) -> Result<Pattern, Diagnostic> {
+    let pattern = parse_tuple_pattern(tokens, options)?;
+
+    if !is_alternative(tokens) || !options.allow_refutable {
+        return Ok(pattern);
+    }
+
+    let mut span = pattern.span;
+    let mut patterns = vec![pattern];
+
+    while is_alternative(tokens) {
+        consume_newlines(tokens);
+        tokens.consume();
+        consume_newlines(tokens);
+
+        let pattern = parse_tuple_pattern(tokens, options)?;
+        span = span.join(pattern.span);
+        patterns.push(pattern);
+    }
+
+    let kind = PatternKind::Or(patterns);
+    Ok(Pattern { kind, span })
+}
+
+fn parse_tuple_pattern(tokens: &mut TokenStream, options: Options) -> Result<Pattern, Diagnostic> {
+    let pattern = parse_pattern_term(tokens, options)?;
 
     if !tokens.is(&Token::Comma) {
         return Ok(pattern);
@@ -147,7 +336,7 @@ fn parse_pattern_impl(
     while tokens.is(&Token::Comma) {
         tokens.consume();
 
-        let pattern = parse_pattern_term(tokens, allow_refutable)?;
+        let pattern = parse_pattern_term(tokens, options)?;
         span = span.join(pattern.span);
         patterns.push(pattern);
     }
@@ -156,10 +345,40 @@ fn parse_pattern_impl(
     Ok(Pattern { kind, span })
 }
 
+/// Parse an optional `: type` after `pattern`.
+pub fn parse_pattern_ascription(
+    tokens: &mut TokenStream,
+    pattern: Pattern,
+) -> Result<Pattern, Diagnostic> {
+    if !tokens.is(&Token::Colon) {
+        return Ok(pattern);
+    }
+
+    tokens.consume();
+
+    let ty = parse_type(tokens)?;
+
+    let span = pattern.span.join(ty.span);
+    let kind = PatternKind::Ascription(Box::new(pattern), ty);
+    Ok(Pattern { kind, span })
+}
+
 pub fn parse_pattern(tokens: &mut TokenStream) -> Result<Pattern, Diagnostic> {
-    parse_pattern_impl(tokens, true)
+    let options = Options {
+        allow_refutable: true,
+        allow_record: true,
+    };
+
+    parse_pattern_impl(tokens, options)
 }
 
+/// Parse the pattern of a parameter, where a record pattern must be wrapped in
+/// parentheses, since `fn f x { .. }` starts the body of `f`.
 pub fn parse_irrefutable_pattern(tokens: &mut TokenStream) -> Result<Pattern, Diagnostic> {
-    parse_pattern_impl(tokens, false)
+    let options = Options {
+        allow_refutable: false,
+        allow_record: false,
+    };
+
+    parse_pattern_impl(tokens, options)
 }
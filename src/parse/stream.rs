@@ -1,6 +1,6 @@
 use std::{fmt, sync::Arc};
 
-use crate::diagnostic::{Diagnostic, Span};
+use crate::diagnostic::{Diagnostic, Span, code};
 
 use super::Token;
 
@@ -80,7 +80,9 @@ impl TokenStream {
             Ok(span)
         } else {
             let message = format!("expected token: `{expected}`");
-            let diagnostic = Diagnostic::error(message).with_span(span);
+            let diagnostic = Diagnostic::error(message)
+                .with_code(code::E0002)
+                .with_span(span);
             Err(diagnostic)
         }
     }
@@ -14,9 +14,13 @@ pub enum Token {
 
     /* keywords */
     And,   // 'and'
+    As,    // 'as'
     Bool,  // 'bool'
+    Else,  // 'else'
     False, // 'false'
+    If,    // 'if'
     Let,   // 'let'
+    Not,   // 'not'
     True,  // 'true'
     Try,   // 'try'
     Int,   // 'int'
@@ -25,6 +29,9 @@ pub enum Token {
     Match, // 'match'
     With,  // 'with'
 
+    /* three-character symbols */
+    DotDotEq, // '..='
+
     /* two-character symbols */
     DotDot,     // '..'
     RArrow,     // '->'
@@ -38,6 +45,7 @@ pub enum Token {
     PipeGt,     // '|>'
     LtLt,       // '<<'
     GtGt,       // '>>'
+    PlusPlus,   // '++'
 
     /* one-character symbols */
     Semi,      // ';'
@@ -84,9 +92,13 @@ impl fmt::Display for Token {
 
             /* keywords */
             Token::And => write!(f, "and"),
+            Token::As => write!(f, "as"),
+            Token::Else => write!(f, "else"),
             Token::Bool => write!(f, "bool"),
             Token::False => write!(f, "false"),
+            Token::If => write!(f, "if"),
             Token::Let => write!(f, "let"),
+            Token::Not => write!(f, "not"),
             Token::True => write!(f, "true"),
             Token::Try => write!(f, "try"),
             Token::Int => write!(f, "int"),
@@ -95,6 +107,9 @@ impl fmt::Display for Token {
             Token::Match => write!(f, "match"),
             Token::With => write!(f, "with"),
 
+            /* three-character symbols */
+            Token::DotDotEq => write!(f, "..="),
+
             /* two-character symbols */
             Token::DotDot => write!(f, ".."),
             Token::RArrow => write!(f, "->"),
@@ -108,6 +123,7 @@ impl fmt::Display for Token {
             Token::PipeGt => write!(f, "|>"),
             Token::LtLt => write!(f, "<<"),
             Token::GtGt => write!(f, ">>"),
+            Token::PlusPlus => write!(f, "++"),
 
             /* one-character symbols */
             Token::Semi => write!(f, ";"),
@@ -149,9 +165,13 @@ impl FromStr for Token {
         Ok(match s {
             /* keywords */
             "and" => Token::And,
+            "as" => Token::As,
+            "else" => Token::Else,
             "bool" => Token::Bool,
             "false" => Token::False,
+            "if" => Token::If,
             "let" => Token::Let,
+            "not" => Token::Not,
             "true" => Token::True,
             "try" => Token::Try,
             "int" => Token::Int,
@@ -160,6 +180,9 @@ impl FromStr for Token {
             "match" => Token::Match,
             "with" => Token::With,
 
+            /* three-character symbols */
+            "..=" => Token::DotDotEq,
+
             /* two-character symbols */
             ".." => Token::DotDot,
             "->" => Token::RArrow,
@@ -173,6 +196,7 @@ impl FromStr for Token {
             "|>" => Token::PipeGt,
             "<<" => Token::LtLt,
             ">>" => Token::GtGt,
+            "++" => Token::PlusPlus,
 
             /* one-character symbols */
             ";" => Token::Semi,
@@ -1,6 +1,6 @@
 use crate::{
     ast::{Type, TypeKind},
-    diagnostic::Diagnostic,
+    diagnostic::{Diagnostic, code},
 };
 
 use super::{Token, TokenStream, parse_ident, parse_path};
@@ -22,7 +22,7 @@ fn is_type_term(tokens: &TokenStream) -> bool {
     )
 }
 
-fn parse_term_type(tokens: &mut TokenStream) -> Result<Type, Diagnostic> {
+pub fn parse_term_type(tokens: &mut TokenStream) -> Result<Type, Diagnostic> {
     let (token, span) = tokens.peek();
 
     match token {
@@ -99,7 +99,9 @@ fn parse_term_type(tokens: &mut TokenStream) -> Result<Type, Diagnostic> {
         }
 
         _ => {
-            let diagnostic = Diagnostic::error("expected type").with_span(span);
+            let diagnostic = Diagnostic::error("expected type")
+                .with_code(code::E0002)
+                .with_span(span);
             Err(diagnostic)
         }
     }
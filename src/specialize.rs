@@ -5,7 +5,7 @@ use std::{
 };
 
 use crate::{
-    diagnostic::Emitter,
+    diagnostic::{Diagnostic, Emitter, Span, code},
     ir::{typed as tir, untyped as uir},
 };
 
@@ -29,6 +29,7 @@ pub fn specialize(
         emitter,
         bodies: HashMap::new(),
         types: HashMap::new(),
+        origins: HashMap::new(),
         uir,
         tir: tir::Program::new(),
     };
@@ -49,6 +50,7 @@ struct Specializer<'a> {
     emitter: &'a mut dyn Emitter,
     bodies: Bodies,
     types: Types,
+    origins: HashMap<tir::Tid, uir::Tid>,
     uir: uir::Program,
     tir: tir::Program,
 }
@@ -74,6 +76,7 @@ impl Specializer<'_> {
 
             locals.push(tir::Local {
                 name: local.name.clone(),
+                span: local.span,
                 ty,
             });
         }
@@ -89,6 +92,7 @@ impl Specializer<'_> {
 
         let tir_bid = self.tir.bodies.push(tir::Body {
             name: self.uir[bid].name.clone(),
+            span: self.uir[bid].span,
             locals,
             inputs,
             expr: None,
@@ -128,6 +132,15 @@ impl Specializer<'_> {
             }
 
             uir::ExprKind::Body(bid) => {
+                let bid = match self.uir.tcx.method(bid) {
+                    Some((trid, method)) => {
+                        let method = method.clone();
+                        self.resolve_method(trid, method, &expected, expr.span)?
+                    }
+
+                    None => bid,
+                };
+
                 let ty = self.uir.tcx.substitute(self.uir[bid].ty.clone());
                 let generics = Self::extract_generics(ty, expected.clone());
                 let bid = self.specialize_body(bid, generics)?;
@@ -156,6 +169,11 @@ impl Specializer<'_> {
                 tir::ExprKind::Try(Box::new(value))
             }
 
+            uir::ExprKind::Unary(op, value) => {
+                let value = self.specialize_expr(*value, generics)?;
+                tir::ExprKind::Unary(op, Box::new(value))
+            }
+
             uir::ExprKind::Call(target, input) => {
                 let target = self.specialize_expr(*target, generics)?;
                 let input = self.specialize_expr(*input, generics)?;
@@ -165,6 +183,8 @@ impl Specializer<'_> {
 
             uir::ExprKind::ListEmpty => tir::ExprKind::ListEmpty,
 
+            uir::ExprKind::Hole => unreachable!("holes are rejected during lowering"),
+
             uir::ExprKind::ListCons(head, tail) => {
                 let head = self.specialize_expr(*head, generics)?;
                 let tail = self.specialize_expr(*tail, generics)?;
@@ -210,7 +230,34 @@ impl Specializer<'_> {
                 let left = self.specialize_expr(*lhs, generics)?;
                 let right = self.specialize_expr(*rhs, generics)?;
 
-                tir::ExprKind::Binary(op, Box::new(left), Box::new(right))
+                let ordered = matches!(
+                    op,
+                    uir::BinOp::Lt | uir::BinOp::Le | uir::BinOp::Gt | uir::BinOp::Ge
+                );
+
+                match self.uir.tcx.lang.ord {
+                    // `a < b` for a type implementing `std::ord` is `compare a b < 0`
+                    Some(ord) if ordered => match self.specialize_impl(ord, &left.ty)?.pop() {
+                        Some((_, compare, ty)) => {
+                            let span = expr.span;
+                            let kind = tir::ExprKind::Body(compare);
+                            let compare = tir::Expr { kind, span, ty };
+
+                            let compared = Self::call(Self::call(compare, left), right);
+                            let zero = tir::Expr {
+                                kind: tir::ExprKind::Int(0),
+                                span,
+                                ty: tir::Type::Int,
+                            };
+
+                            tir::ExprKind::Binary(op, Box::new(compared), Box::new(zero))
+                        }
+
+                        None => tir::ExprKind::Binary(op, Box::new(left), Box::new(right)),
+                    },
+
+                    _ => tir::ExprKind::Binary(op, Box::new(left), Box::new(right)),
+                }
             }
 
             uir::ExprKind::Field(target, name) => {
@@ -225,8 +272,18 @@ impl Specializer<'_> {
                 let mut new_arms = Vec::new();
                 for arm in arms {
                     let pattern = self.specialize_pattern(arm.pattern, generics)?;
+
+                    let guard = match arm.guard {
+                        Some(guard) => Some(self.specialize_expr(guard, generics)?),
+                        None => None,
+                    };
+
                     let expr = self.specialize_expr(arm.expr, generics)?;
-                    new_arms.push(tir::Arm { pattern, expr });
+                    new_arms.push(tir::Arm {
+                        pattern,
+                        guard,
+                        expr,
+                    });
                 }
 
                 tir::ExprKind::Match(Box::new(target), new_arms)
@@ -275,8 +332,14 @@ impl Specializer<'_> {
 
             uir::PatternKind::Int(value) => tir::PatternKind::Int(value),
             uir::PatternKind::Bool(value) => tir::PatternKind::Bool(value),
+            uir::PatternKind::Range(lower, upper) => tir::PatternKind::Range(lower, upper),
             uir::PatternKind::String(value) => tir::PatternKind::String(value),
 
+            uir::PatternKind::Prefix(prefix, rest) => {
+                let rest = self.specialize_pattern(*rest, generics)?;
+                tir::PatternKind::Prefix(prefix, Box::new(rest))
+            }
+
             uir::PatternKind::Variant(ty, name, value) => {
                 let ty = self.specialize_type(ty, generics)?;
 
@@ -295,6 +358,35 @@ impl Specializer<'_> {
 
                 tir::PatternKind::ListCons(Box::new(head), Box::new(tail))
             }
+
+            uir::PatternKind::Record(ty, fields) => {
+                let ty = self.specialize_type(ty, generics)?;
+
+                let mut new_fields = Vec::new();
+
+                for (name, pattern) in fields {
+                    let pattern = self.specialize_pattern(pattern, generics)?;
+                    new_fields.push((name, pattern));
+                }
+
+                tir::PatternKind::Record(ty, new_fields)
+            }
+
+            uir::PatternKind::Or(patterns) => {
+                let mut new_patterns = Vec::new();
+
+                for pattern in patterns {
+                    let pattern = self.specialize_pattern(pattern, generics)?;
+                    new_patterns.push(pattern);
+                }
+
+                tir::PatternKind::Or(new_patterns)
+            }
+
+            uir::PatternKind::As(pattern, lid) => {
+                let pattern = self.specialize_pattern(*pattern, generics)?;
+                tir::PatternKind::As(Box::new(pattern), lid.cast())
+            }
         };
 
         Ok(tir::Pattern {
@@ -379,7 +471,8 @@ impl Specializer<'_> {
                     .types
                     .push_newtype(tir::Newtype::Record(tir::Record::default()));
 
-                self.types.insert((tid, arguments), tir_tid);
+                self.types.insert((tid, arguments.clone()), tir_tid);
+                self.origins.insert(tir_tid, tid);
 
                 let mut fields = Vec::new();
 
@@ -394,6 +487,8 @@ impl Specializer<'_> {
                 let record = tir::Record { fields };
                 self.tir.types[tir_tid] = tir::Newtype::Record(record);
 
+                self.specialize_lang_impls(tir_tid, arguments)?;
+
                 Ok(tir_tid)
             }
 
@@ -403,7 +498,8 @@ impl Specializer<'_> {
                     .types
                     .push_newtype(tir::Newtype::Union(tir::Union::default()));
 
-                self.types.insert((tid, arguments), tir_tid);
+                self.types.insert((tid, arguments.clone()), tir_tid);
+                self.origins.insert(tir_tid, tid);
 
                 let mut variants = Vec::new();
 
@@ -422,6 +518,8 @@ impl Specializer<'_> {
                 let union = tir::Union { variants };
                 self.tir.types[tir_tid] = tir::Newtype::Union(union);
 
+                self.specialize_lang_impls(tir_tid, arguments)?;
+
                 Ok(tir_tid)
             }
 
@@ -430,12 +528,152 @@ impl Specializer<'_> {
                 let tir_tid = self.tir.types.push_newtype(tir::Newtype::Alias(aliased));
 
                 self.types.insert((tid, arguments), tir_tid);
+                self.origins.insert(tir_tid, tid);
 
                 Ok(tir_tid)
             }
         }
     }
 
+    /// Specialize the impls of the built-in traits the runtime calls for the
+    /// newtype `tid`.
+    fn specialize_lang_impls(
+        &mut self,
+        tid: tir::Tid,
+        arguments: Vec<tir::Type>,
+    ) -> Result<(), SpecializeError> {
+        let lang = &self.uir.tcx.lang;
+        let traits = [lang.eq, lang.hash, lang.format];
+        let ty = tir::Type::Newtype(tid, arguments);
+
+        for trid in traits.into_iter().flatten() {
+            for (name, bid, _) in self.specialize_impl(trid, &ty)? {
+                self.tir.methods.entry(tid).or_default().push((name, bid));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Specialize the methods of the impl of `trid` for `ty`, if there is one,
+    /// returning their names, bodies and types.
+    fn specialize_impl(
+        &mut self,
+        trid: uir::Trid,
+        ty: &tir::Type,
+    ) -> Result<Vec<(String, tir::Bid, tir::Type)>, SpecializeError> {
+        let Some(r#impl) = self.find_impl(trid, ty) else {
+            return Ok(Vec::new());
+        };
+
+        let r#trait = self.uir.tcx[trid].clone();
+        let generics = BTreeMap::from([(r#trait.var, ty.clone())]);
+
+        let mut methods = Vec::new();
+
+        for method in r#trait.methods {
+            let expected = self.specialize_type(method.ty, &generics)?;
+
+            let bid = r#impl.methods[&method.name];
+            let body_ty = self.uir.tcx.substitute(self.uir[bid].ty.clone());
+            let body_generics = Self::extract_generics(body_ty, expected.clone());
+
+            let bid = self.specialize_body(bid, body_generics)?;
+            methods.push((method.name, bid, expected));
+        }
+
+        Ok(methods)
+    }
+
+    /// Find the body of the impl that a use of `method` with the type
+    /// `expected` refers to.
+    fn resolve_method(
+        &mut self,
+        trid: uir::Trid,
+        method: uir::Method,
+        expected: &tir::Type,
+        span: Span,
+    ) -> Result<uir::Bid, SpecializeError> {
+        let r#trait = &self.uir.tcx[trid];
+        let generics = Self::extract_generics(method.ty, expected.clone());
+        let ty = generics
+            .get(&r#trait.var)
+            .cloned()
+            .unwrap_or(tir::Type::Unit);
+
+        if let Some(r#impl) = self.find_impl(trid, &ty) {
+            return Ok(r#impl.methods[&method.name]);
+        }
+
+        // without an impl the runtime compares, hashes and formats values by
+        // their contents, through the extern the method is declared as
+        let lang = &self.uir.tcx.lang;
+
+        if [lang.eq, lang.hash, lang.format].contains(&Some(trid)) {
+            return Ok(method.body);
+        }
+
+        let diagnostic = Diagnostic::error(format!(
+            "`{}` is used with a type that does not implement `{}`",
+            method.name, r#trait.name,
+        ))
+        .with_code(code::E0029)
+        .with_label(span, "used here");
+
+        self.emitter.emit(diagnostic);
+        Err(SpecializeError)
+    }
+
+    fn find_impl(&self, trid: uir::Trid, ty: &tir::Type) -> Option<uir::Impl> {
+        let mut ty = ty;
+
+        while let tir::Type::Newtype(tid, _) = ty
+            && let tir::Newtype::Alias(ref aliased) = self.tir.types[*tid]
+        {
+            ty = aliased;
+        }
+
+        let impls = self.uir.tcx.impls().iter();
+        let mut impls = impls.filter(|r#impl| r#impl.r#trait == trid);
+
+        impls
+            .find(|r#impl| {
+                let uir::Type::App(ref head) = r#impl.ty else {
+                    return false;
+                };
+
+                match (&head.kind, ty) {
+                    (uir::AppKind::Int, tir::Type::Int)
+                    | (uir::AppKind::Str, tir::Type::Str)
+                    | (uir::AppKind::Bool, tir::Type::Bool)
+                    | (uir::AppKind::Unit, tir::Type::Unit)
+                    | (uir::AppKind::List(_), tir::Type::List(_))
+                    | (uir::AppKind::Function(..), tir::Type::Function(..)) => true,
+
+                    (uir::AppKind::Tuple(a), tir::Type::Tuple(b)) => a.len() == b.len(),
+                    (uir::AppKind::Newtype(a, _), tir::Type::Newtype(b, _)) => {
+                        self.origins.get(b) == Some(a)
+                    }
+
+                    (_, _) => false,
+                }
+            })
+            .cloned()
+    }
+
+    /// Call the function `target` with `input`.
+    fn call(target: tir::Expr, input: tir::Expr) -> tir::Expr {
+        let tir::Type::Function(_, ref output) = target.ty else {
+            unreachable!("only functions can be called");
+        };
+
+        tir::Expr {
+            ty: (**output).clone(),
+            span: input.span,
+            kind: tir::ExprKind::Call(Box::new(target), Box::new(input)),
+        }
+    }
+
     fn extract_generics(ty: uir::Type, expected: tir::Type) -> BTreeMap<uir::Var, tir::Type> {
         let mut generics = BTreeMap::new();
         Self::extract_generics_impl(ty, expected, &mut generics);